@@ -1,11 +1,34 @@
 #version 450
 
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 model;
+    mat4 view;
+    mat4 proj;
+    mat4 normal_matrix;
+    vec4 light_direction;
+    vec4 light_color;
+    float ambient;
+    float time;
+    float delta_time;
+}
+ubo;
+
 layout(binding = 1) uniform sampler2D texSampler;
 
 layout(location = 0) in vec2 fragTexCoord;
+layout(location = 1) in vec3 fragNormal;
 
 layout(location = 0) out vec4 outColor;
 
 void main() {
-    outColor = texture(texSampler, fragTexCoord);
+    vec4 color = texture(texSampler, fragTexCoord);
+
+    // Zero-length normals would turn into NaN when normalized, light them fully instead
+    float diffuse = 1.0;
+    if (length(fragNormal) > 1e-6) {
+        diffuse = max(dot(normalize(fragNormal), -normalize(ubo.light_direction.xyz)), 0.0);
+    }
+
+    vec3 lighting = ubo.ambient + diffuse * ubo.light_color.rgb;
+    outColor = vec4(color.rgb * lighting, color.a);
 }
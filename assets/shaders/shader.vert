@@ -4,15 +4,24 @@ layout(binding = 0) uniform UniformBufferObject {
     mat4 model;
     mat4 view;
     mat4 proj;
+    mat4 normal_matrix;
+    vec4 light_direction;
+    vec4 light_color;
+    float ambient;
+    float time;
+    float delta_time;
 }
 ubo;
 
 layout(location = 0) in vec3 position;
 layout(location = 1) in vec2 texture_coords;
+layout(location = 2) in vec3 normal;
 
 layout(location = 0) out vec2 fragTexCoord;
+layout(location = 1) out vec3 fragNormal;
 
 void main() {
     gl_Position = ubo.proj * ubo.view * ubo.model * vec4(position, 1.0);
     fragTexCoord = texture_coords;
+    fragNormal = mat3(ubo.normal_matrix) * normal;
 }
@@ -0,0 +1,84 @@
+use vulkan_rust_tutorial::{
+    clock::Clock,
+    init::create_instance,
+    renderer::{FrameInput, Renderer, RendererSettings},
+};
+
+use vulkano_win::VkSurfaceBuild;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    window::WindowBuilder,
+};
+
+use nalgebra_glm as glm;
+
+use std::f64::consts::TAU;
+
+use log::error;
+
+use color_eyre::Result;
+
+// The host owns the window and the event loop, the renderer only sees the surface
+fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let (instance, _) = create_instance(false)?;
+
+    let event_loop = EventLoop::new();
+    let surface = WindowBuilder::new()
+        .with_title("Embedded renderer")
+        .build_vk_surface(&event_loop, instance)?;
+
+    let settings = RendererSettings {
+        extent: surface.window().inner_size().into(),
+        model: Some("builtin:cube".into()),
+        texture: Some("builtin:uv-test".into()),
+        ..RendererSettings::default()
+    };
+    let mut renderer = Renderer::with_surface(surface.clone(), &settings)?;
+
+    let mut clock = Clock::new(0.0);
+    let view = glm::look_at(
+        &glm::vec3(2.0, 2.0, 2.0),
+        &glm::vec3(0.0, 0.0, 0.0),
+        &glm::vec3(0.0, 0.0, 1.0),
+    );
+
+    event_loop.run(move |event, _, control_flow| match event {
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } => *control_flow = ControlFlow::Exit,
+        Event::WindowEvent {
+            event: WindowEvent::Resized(size),
+            ..
+        } => renderer.resize(size.into()),
+        Event::MainEventsCleared if *control_flow != ControlFlow::Exit => {
+            // Resized wakes the loop up again once the window is restored
+            if renderer.is_suspended() {
+                *control_flow = ControlFlow::Wait;
+            } else {
+                *control_flow = ControlFlow::Poll;
+                surface.window().request_redraw();
+            }
+        }
+        Event::RedrawRequested(_) => {
+            let frame_time = clock.tick();
+            // Wrapped in f64, the elapsed time loses too much precision as an f32 on long runs
+            let angle = (frame_time.elapsed % TAU) as f32;
+            let input = FrameInput {
+                view,
+                model: glm::rotate_z(&glm::identity(), angle),
+            };
+
+            if let Err(e) = renderer.render_frame(frame_time, input) {
+                error!("Error when rendering a frame: {e:?}");
+                *control_flow = ControlFlow::Exit;
+            }
+        }
+        _ => (),
+    });
+}
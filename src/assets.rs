@@ -0,0 +1,453 @@
+use crate::error::{Error, Result};
+
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use zip::{result::ZipError, ZipArchive};
+
+use log::info;
+
+const BUILTIN_CUBE: &[u8] = include_bytes!("../assets/builtin/cube.obj");
+
+pub fn builtin(path: &str) -> Option<&'static [u8]> {
+    match path {
+        "builtin:cube" => Some(BUILTIN_CUBE),
+        _ => None,
+    }
+}
+
+// The central directory is parsed once when the archive is mounted, clones of the root share it
+#[derive(Debug, Clone)]
+struct Archive {
+    path: PathBuf,
+    zip: Arc<Mutex<ZipArchive<File>>>,
+}
+
+impl Archive {
+    fn open(path: PathBuf) -> Result<Self> {
+        match File::open(&path)
+            .map_err(ZipError::from)
+            .and_then(ZipArchive::new)
+        {
+            Ok(zip) => Ok(Self {
+                path,
+                zip: Arc::new(Mutex::new(zip)),
+            }),
+            Err(source) => Err(Error::ArchiveMount { path, source }),
+        }
+    }
+}
+
+impl PartialEq for Archive {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Eq for Archive {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Root {
+    Directory(PathBuf),
+    Archive(Archive),
+}
+
+impl Root {
+    fn from_path(path: PathBuf) -> Result<Self> {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("zip") => {
+                Ok(Root::Archive(Archive::open(path)?))
+            }
+            _ => Ok(Root::Directory(path)),
+        }
+    }
+}
+
+enum Location {
+    File(PathBuf),
+    Entry { archive: Archive, name: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetRoot {
+    roots: Vec<Root>,
+}
+
+impl AssetRoot {
+    // The archive is searched right after the explicit root, so loose files there override it.
+    // Archives are opened here, so a missing or corrupt one is reported before any lookup
+    pub fn new(explicit_root: Option<PathBuf>, archive: Option<PathBuf>) -> Result<Self> {
+        let mut roots = explicit_root
+            .into_iter()
+            .map(Root::from_path)
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(archive) = archive {
+            roots.push(Root::Archive(Archive::open(archive)?));
+        }
+        roots.extend(default_roots());
+
+        Ok(Self::from_roots(roots))
+    }
+
+    fn from_roots(roots: Vec<Root>) -> Self {
+        Self { roots }
+    }
+
+    // Only loose files have a path, e.g. for the shader watcher
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        let path = path.as_ref();
+
+        match self.locate(path)? {
+            Location::File(resolved) => Ok(resolved),
+            Location::Entry { archive, .. } => Err(Error::AssetInArchive {
+                path: path.to_owned(),
+                archive: archive.path,
+            }),
+        }
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>> {
+        match self.locate(path.as_ref())? {
+            Location::File(resolved) => Ok(std::fs::read(resolved)?),
+            Location::Entry { archive, name } => {
+                let mut zip = archive.zip.lock().unwrap();
+                let mut entry = zip.by_name(&name)?;
+
+                let mut bytes = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn locate(&self, path: &Path) -> Result<Location> {
+        if path.is_absolute() {
+            if path.exists() {
+                return Ok(Location::File(path.to_owned()));
+            }
+            return Err(Error::AssetNotFound {
+                path: path.to_owned(),
+                attempted: vec![path.to_owned()],
+                near_misses: Vec::new(),
+            });
+        }
+
+        let name = entry_name(path);
+        let mut attempted = Vec::new();
+        let mut near_misses = Vec::new();
+
+        for root in &self.roots {
+            match root {
+                Root::Directory(dir) => {
+                    let candidate = dir.join(path);
+                    if candidate.exists() {
+                        info!(
+                            "Resolved asset {} to {}",
+                            path.display(),
+                            candidate.display()
+                        );
+                        return Ok(Location::File(candidate));
+                    }
+                    attempted.push(candidate);
+                }
+                Root::Archive(archive) => {
+                    let zip = archive.zip.lock().unwrap();
+                    if zip.file_names().any(|entry| entry == name) {
+                        info!(
+                            "Resolved asset {} to {name} in {}",
+                            path.display(),
+                            archive.path.display()
+                        );
+                        return Ok(Location::Entry {
+                            archive: archive.clone(),
+                            name,
+                        });
+                    }
+                    near_misses.extend(
+                        zip.file_names()
+                            .filter(|entry| is_near_miss(entry, &name))
+                            .map(str::to_owned),
+                    );
+                    attempted.push(archive.path.join(&name));
+                }
+            }
+        }
+
+        Err(Error::AssetNotFound {
+            path: path.to_owned(),
+            attempted,
+            near_misses,
+        })
+    }
+}
+
+impl Default for AssetRoot {
+    fn default() -> Self {
+        Self::from_roots(default_roots())
+    }
+}
+
+fn default_roots() -> Vec<Root> {
+    let mut roots = Vec::new();
+
+    if let Some(executable_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_owned))
+    {
+        roots.push(Root::Directory(executable_dir));
+    }
+
+    if let Ok(current_dir) = std::env::current_dir() {
+        roots.push(Root::Directory(current_dir));
+    }
+
+    roots
+}
+
+// Archive entries always use forward slashes, whatever the platform the path was written on
+fn entry_name(path: &Path) -> String {
+    path.to_string_lossy()
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Entries are matched case-sensitively, so the same name in another case or directory is likely
+// what was meant
+fn is_near_miss(entry: &str, name: &str) -> bool {
+    let file_name = |path: &str| path.rsplit('/').next().unwrap_or_default().to_lowercase();
+    entry.eq_ignore_ascii_case(name)
+        || (!entry.ends_with('/') && file_name(entry) == file_name(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{fs, io::Write};
+
+    use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+    fn write_archive(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = ZipWriter::new(File::create(path).unwrap());
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+        for (name, contents) in entries {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn mount(path: PathBuf) -> Archive {
+        Archive::open(path).unwrap()
+    }
+
+    #[test]
+    fn explicit_root_comes_before_executable_and_current_dirs() {
+        let explicit_root = PathBuf::from("explicit");
+        let asset_root = AssetRoot::new(Some(explicit_root.clone()), None).unwrap();
+
+        let executable_dir = std::env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_owned();
+        let current_dir = std::env::current_dir().unwrap();
+
+        assert_eq!(
+            asset_root.roots,
+            [
+                Root::Directory(explicit_root),
+                Root::Directory(executable_dir),
+                Root::Directory(current_dir)
+            ]
+        );
+    }
+
+    #[test]
+    fn first_root_containing_the_asset_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let roots: Vec<_> = ["explicit", "executable", "current"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        for root in &roots {
+            fs::create_dir_all(root.join("models")).unwrap();
+        }
+        let asset_root =
+            AssetRoot::from_roots(roots.iter().cloned().map(Root::Directory).collect());
+
+        fs::write(roots[2].join("models/cube.obj"), "").unwrap();
+        assert_eq!(
+            asset_root.resolve("models/cube.obj").unwrap(),
+            roots[2].join("models/cube.obj")
+        );
+
+        fs::write(roots[1].join("models/cube.obj"), "").unwrap();
+        assert_eq!(
+            asset_root.resolve("models/cube.obj").unwrap(),
+            roots[1].join("models/cube.obj")
+        );
+
+        fs::write(roots[0].join("models/cube.obj"), "").unwrap();
+        assert_eq!(
+            asset_root.resolve("models/cube.obj").unwrap(),
+            roots[0].join("models/cube.obj")
+        );
+    }
+
+    #[test]
+    fn absolute_paths_ignore_the_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cube.obj");
+        fs::write(&path, "").unwrap();
+
+        let asset_root = AssetRoot::from_roots(vec![Root::Directory(PathBuf::from("unused"))]);
+        assert_eq!(asset_root.resolve(&path).unwrap(), path);
+    }
+
+    #[test]
+    fn builtin_assets_only_match_builtin_paths() {
+        assert!(builtin("builtin:cube").is_some());
+        assert!(builtin("models/cube.obj").is_none());
+    }
+
+    #[test]
+    fn missing_asset_reports_every_attempted_location() {
+        let root = std::env::temp_dir().join("vulkan-rust-tutorial-missing-root");
+        let asset_root = AssetRoot::new(Some(root.clone()), None).unwrap();
+
+        match asset_root.resolve("models/missing.obj") {
+            Err(Error::AssetNotFound {
+                path, attempted, ..
+            }) => {
+                assert_eq!(path, Path::new("models/missing.obj"));
+                assert_eq!(attempted.len(), asset_root.roots.len());
+                assert_eq!(attempted[0], root.join("models/missing.obj"));
+            }
+            other => panic!("expected AssetNotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn archive_entries_are_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("assets.zip");
+        write_archive(&archive, &[("models/cube.obj", "v 0 0 0")]);
+
+        let asset_root = AssetRoot::from_roots(vec![Root::Archive(mount(archive))]);
+        assert_eq!(asset_root.read("models/cube.obj").unwrap(), b"v 0 0 0");
+        assert_eq!(asset_root.read("./models\\cube.obj").unwrap(), b"v 0 0 0");
+    }
+
+    #[test]
+    fn zip_roots_are_mounted_as_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("assets.zip");
+        write_archive(
+            &archive,
+            &[("textures/white.ppm", "P3 1 1 255 255 255 255")],
+        );
+
+        let asset_root = AssetRoot::new(Some(archive.clone()), None).unwrap();
+        assert_eq!(asset_root.roots[0], Root::Archive(mount(archive)));
+        assert!(asset_root.read("textures/white.ppm").is_ok());
+    }
+
+    #[test]
+    fn loose_files_override_archive_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("assets.zip");
+        write_archive(
+            &archive,
+            &[
+                ("models/cube.obj", "archived"),
+                ("models/sphere.obj", "archived"),
+            ],
+        );
+
+        let loose = dir.path().join("loose");
+        fs::create_dir_all(loose.join("models")).unwrap();
+        fs::write(loose.join("models/cube.obj"), "loose").unwrap();
+
+        let asset_root =
+            AssetRoot::from_roots(vec![Root::Directory(loose), Root::Archive(mount(archive))]);
+        assert_eq!(asset_root.read("models/cube.obj").unwrap(), b"loose");
+        assert_eq!(asset_root.read("models/sphere.obj").unwrap(), b"archived");
+    }
+
+    #[test]
+    fn archive_entries_have_no_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("assets.zip");
+        write_archive(&archive, &[("shaders/shader.vert", "")]);
+
+        let asset_root = AssetRoot::from_roots(vec![Root::Archive(mount(archive))]);
+        assert!(matches!(
+            asset_root.resolve("shaders/shader.vert"),
+            Err(Error::AssetInArchive { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_archive_entry_lists_near_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("assets.zip");
+        write_archive(
+            &archive,
+            &[
+                ("models/Cube.obj", ""),
+                ("backup/cube.obj", ""),
+                ("models/sphere.obj", ""),
+            ],
+        );
+
+        let asset_root = AssetRoot::from_roots(vec![Root::Archive(mount(archive.clone()))]);
+        match asset_root.read("models/cube.obj") {
+            Err(Error::AssetNotFound {
+                attempted,
+                mut near_misses,
+                ..
+            }) => {
+                near_misses.sort();
+                assert_eq!(near_misses, ["backup/cube.obj", "models/Cube.obj"]);
+                assert_eq!(attempted, [archive.join("models/cube.obj")]);
+            }
+            other => panic!("expected AssetNotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bad_archives_are_reported_when_mounted() {
+        let dir = tempfile::tempdir().unwrap();
+        let corrupt = dir.path().join("corrupt.zip");
+        fs::write(&corrupt, "not a zip").unwrap();
+        let missing = dir.path().join("missing.zip");
+
+        for archive in [corrupt, missing] {
+            match AssetRoot::new(None, Some(archive.clone())) {
+                Err(Error::ArchiveMount { path, .. }) => assert_eq!(path, archive),
+                other => panic!("expected ArchiveMount, got {other:?}"),
+            }
+            assert!(AssetRoot::new(Some(archive), None).is_err());
+        }
+    }
+
+    #[test]
+    fn archive_is_parsed_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("assets.zip");
+        write_archive(&archive, &[("models/cube.obj", "v 0 0 0")]);
+
+        let asset_root = AssetRoot::from_roots(vec![Root::Archive(mount(archive.clone()))]);
+        fs::remove_file(&archive).unwrap();
+
+        assert_eq!(asset_root.read("models/cube.obj").unwrap(), b"v 0 0 0");
+    }
+}
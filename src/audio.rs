@@ -0,0 +1,216 @@
+use crate::assets::AssetRoot;
+#[cfg(any(feature = "audio", test))]
+use crate::error::Result;
+
+#[cfg(feature = "audio")]
+use std::{collections::HashSet, io::Cursor};
+
+#[cfg(feature = "audio")]
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+
+#[cfg(feature = "audio")]
+use log::{info, warn};
+
+// Equal volume steps sound equally loud when they are equal in decibels, the lowest step is -60 dB
+const VOLUME_RANGE_DB: f32 = 60.0;
+
+// Bare file names are looked up in the sounds directory, other paths are used as is
+pub fn sound_path(name: &str) -> String {
+    if name.contains('/') {
+        name.to_owned()
+    } else {
+        format!("assets/sounds/{name}")
+    }
+}
+
+pub fn gain(volume: f32, muted: bool) -> f32 {
+    let volume = volume.clamp(0.0, 1.0);
+    if muted || volume == 0.0 {
+        0.0
+    } else {
+        10f32.powf((volume - 1.0) * VOLUME_RANGE_DB / 20.0)
+    }
+}
+
+#[cfg(any(feature = "audio", test))]
+fn load_sound(asset_root: &AssetRoot, name: &str) -> Result<Vec<u8>> {
+    asset_root.read(sound_path(name))
+}
+
+#[cfg(feature = "audio")]
+pub struct AudioSystem {
+    asset_root: AssetRoot,
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    ambient: Option<Sink>,
+    volume: f32,
+    muted: bool,
+    missing: HashSet<String>,
+}
+
+#[cfg(feature = "audio")]
+impl AudioSystem {
+    // Without an output device the sounds are skipped, the renderer doesn't depend on them
+    pub fn new(asset_root: AssetRoot, volume: f32, muted: bool) -> Self {
+        let output = match OutputStream::try_default() {
+            Ok(output) => Some(output),
+            Err(e) => {
+                warn!("No audio output, sounds are disabled: {e}");
+                None
+            }
+        };
+
+        Self {
+            asset_root,
+            output,
+            ambient: None,
+            volume,
+            muted,
+            missing: HashSet::new(),
+        }
+    }
+
+    pub fn play(&mut self, name: &str) {
+        let gain = gain(self.volume, self.muted);
+        if self.output.is_none() || gain == 0.0 {
+            return;
+        }
+
+        let source = match self.decode(name) {
+            Some(source) => source,
+            None => return,
+        };
+        if let Some((_, handle)) = &self.output {
+            if let Err(e) = handle.play_raw(source.amplify(gain).convert_samples()) {
+                warn!("Failed to play {name}: {e}");
+            }
+        }
+    }
+
+    // Replaces the current ambient track, which loops until it is replaced or audio shuts down
+    pub fn play_ambient(&mut self, name: &str) {
+        if self.output.is_none() {
+            return;
+        }
+        let source = match self.decode(name) {
+            Some(source) => source,
+            None => return,
+        };
+
+        let sink = match &self.output {
+            Some((_, handle)) => match Sink::try_new(handle) {
+                Ok(sink) => sink,
+                Err(e) => {
+                    warn!("Failed to play {name}: {e}");
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        sink.set_volume(gain(self.volume, self.muted));
+        sink.append(source.repeat_infinite());
+        self.ambient = Some(sink);
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        if let Some(ambient) = &self.ambient {
+            ambient.set_volume(gain(self.volume, self.muted));
+        }
+        info!("Audio {}", if self.muted { "muted" } else { "unmuted" });
+    }
+
+    // winit exits the process without dropping the event loop closure, so the output stream and
+    // its thread are stopped here
+    pub fn shutdown(&mut self) {
+        if let Some(ambient) = self.ambient.take() {
+            ambient.stop();
+        }
+        self.output = None;
+    }
+
+    // A missing or broken sound is only reported once, the cue is skipped
+    fn decode(&mut self, name: &str) -> Option<Decoder<Cursor<Vec<u8>>>> {
+        let decoded = load_sound(&self.asset_root, name)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Decoder::new(Cursor::new(bytes)).map_err(|e| e.to_string()));
+
+        match decoded {
+            Ok(source) => Some(source),
+            Err(e) => {
+                if self.missing.insert(name.to_owned()) {
+                    warn!("Sound {name} is unavailable: {e}");
+                }
+                None
+            }
+        }
+    }
+}
+
+// Built without the audio feature the cues are dropped, so hosts don't need to gate their calls
+#[cfg(not(feature = "audio"))]
+pub struct AudioSystem;
+
+#[cfg(not(feature = "audio"))]
+impl AudioSystem {
+    pub fn new(_asset_root: AssetRoot, _volume: f32, _muted: bool) -> Self {
+        Self
+    }
+
+    pub fn play(&mut self, _name: &str) {}
+
+    pub fn play_ambient(&mut self, _name: &str) {}
+
+    pub fn toggle_mute(&mut self) {}
+
+    pub fn shutdown(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use crate::error::Error;
+
+    #[test]
+    fn bare_names_are_looked_up_in_the_sounds_directory() {
+        assert_eq!(sound_path("click.ogg"), "assets/sounds/click.ogg");
+        assert_eq!(
+            sound_path("assets/music/theme.ogg"),
+            "assets/music/theme.ogg"
+        );
+    }
+
+    #[test]
+    fn sounds_are_read_through_the_asset_root() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("assets/sounds")).unwrap();
+        fs::write(root.path().join("assets/sounds/click.ogg"), b"click").unwrap();
+
+        let asset_root = AssetRoot::new(Some(root.path().to_owned()), None).unwrap();
+
+        assert_eq!(load_sound(&asset_root, "click.ogg").unwrap(), b"click");
+        assert!(matches!(
+            load_sound(&asset_root, "missing.ogg"),
+            Err(Error::AssetNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn volume_curve_is_linear_in_decibels() {
+        assert_eq!(gain(1.0, false), 1.0);
+        assert_eq!(gain(0.0, false), 0.0);
+        assert!((gain(0.5, false) - 10f32.powf(-1.5)).abs() < 1e-6);
+        assert!((gain(2.0 / 3.0, false) / gain(1.0 / 3.0, false) - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn volume_is_clamped_and_muting_silences() {
+        assert_eq!(gain(1.5, false), 1.0);
+        assert_eq!(gain(-0.5, false), 0.0);
+        assert_eq!(gain(1.0, true), 0.0);
+        assert_eq!(gain(0.5, true), 0.0);
+    }
+}
@@ -0,0 +1,142 @@
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, MouseButton, MouseScrollDelta},
+};
+
+use nalgebra_glm as glm;
+
+const MIN_DISTANCE: f32 = 1.0;
+const MAX_DISTANCE: f32 = 8.0;
+
+// Just under 90 degrees, so the view direction never becomes parallel to the up vector
+const MAX_PITCH: f32 = 1.55;
+
+const DEFAULT_ROTATION_SPEED: f32 = 0.005;
+const DEFAULT_ZOOM_SPEED: f32 = 0.1;
+const PIXELS_PER_LINE: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CameraSettings {
+    pub look_sensitivity: Option<f32>,
+    pub invert_y: bool,
+    pub zoom_speed: Option<f32>,
+}
+
+pub struct Camera {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    dragging: bool,
+    cursor_position: Option<PhysicalPosition<f64>>,
+    rotation_speed: f32,
+    pitch_direction: f32,
+    zoom_factor: f32,
+}
+
+impl Camera {
+    pub fn new(settings: CameraSettings) -> Self {
+        // Same eye position as the previous fixed look_at((2, 2, 2), origin, z-up)
+        Self {
+            yaw: f32::to_radians(45.0),
+            pitch: f32::asin(1.0 / f32::sqrt(3.0)),
+            distance: f32::sqrt(12.0),
+            dragging: false,
+            cursor_position: None,
+            rotation_speed: settings
+                .look_sensitivity
+                .map_or(DEFAULT_ROTATION_SPEED, f32::to_radians),
+            pitch_direction: if settings.invert_y { -1.0 } else { 1.0 },
+            zoom_factor: 1.0 - settings.zoom_speed.unwrap_or(DEFAULT_ZOOM_SPEED),
+        }
+    }
+
+    pub fn mouse_input(&mut self, state: ElementState, button: MouseButton) {
+        if button == MouseButton::Left {
+            self.dragging = state == ElementState::Pressed;
+        }
+    }
+
+    // The button release is never delivered if it happens outside of the window, e.g. after an
+    // alt-tab in the middle of a drag
+    pub fn release_drag(&mut self) {
+        self.dragging = false;
+    }
+
+    // Returns whether the camera moved, hovering without dragging doesn't count as input
+    pub fn cursor_moved(&mut self, position: PhysicalPosition<f64>) -> bool {
+        // winit only reports absolute positions, so the drag delta comes from the previous event
+        let moved = match self.cursor_position {
+            Some(previous) if self.dragging => {
+                let dx = (position.x - previous.x) as f32;
+                let dy = (position.y - previous.y) as f32;
+
+                self.yaw -= dx * self.rotation_speed;
+                self.pitch = (self.pitch + self.pitch_direction * dy * self.rotation_speed)
+                    .clamp(-MAX_PITCH, MAX_PITCH);
+                true
+            }
+            _ => false,
+        };
+        self.cursor_position = Some(position);
+        moved
+    }
+
+    pub fn mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+        };
+
+        self.distance =
+            (self.distance * self.zoom_factor.powf(lines)).clamp(MIN_DISTANCE, MAX_DISTANCE);
+    }
+
+    pub fn view_matrix(&self) -> glm::Mat4 {
+        let eye = glm::vec3(
+            self.distance * self.pitch.cos() * self.yaw.cos(),
+            self.distance * self.pitch.cos() * self.yaw.sin(),
+            self.distance * self.pitch.sin(),
+        );
+
+        glm::look_at(&eye, &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(CameraSettings {
+            look_sensitivity: None,
+            invert_y: false,
+            zoom_speed: None,
+        })
+    }
+
+    #[test]
+    fn released_drag_stops_orbiting() {
+        let mut camera = camera();
+        camera.cursor_moved(PhysicalPosition::new(0.0, 0.0));
+        camera.mouse_input(ElementState::Pressed, MouseButton::Left);
+
+        camera.release_drag();
+        let yaw = camera.yaw;
+        camera.cursor_moved(PhysicalPosition::new(100.0, 0.0));
+        assert_eq!(camera.yaw, yaw);
+    }
+
+    #[test]
+    fn only_drags_move_the_camera() {
+        let mut camera = camera();
+
+        assert!(!camera.cursor_moved(PhysicalPosition::new(0.0, 0.0)));
+        assert!(!camera.cursor_moved(PhysicalPosition::new(10.0, 0.0)));
+
+        camera.mouse_input(ElementState::Pressed, MouseButton::Left);
+        assert!(camera.cursor_moved(PhysicalPosition::new(20.0, 0.0)));
+
+        camera.mouse_input(ElementState::Released, MouseButton::Left);
+        assert!(!camera.cursor_moved(PhysicalPosition::new(30.0, 0.0)));
+    }
+}
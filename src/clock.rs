@@ -0,0 +1,186 @@
+use std::time::Instant;
+
+// Shader time only, the turntable keeps its own f64 angle. One hour is a multiple of any period
+// that divides it (1 s, 4 s, 1 min...), so shader animations with such a period don't pop on wrap.
+pub const SHADER_TIME_PERIOD: f64 = 3600.0;
+
+const MIN_SPEED: f64 = 1.0 / 16.0;
+const MAX_SPEED: f64 = 16.0;
+
+// The wall delta is neither paused nor scaled, for timers that follow real time
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTime {
+    pub elapsed: f64,
+    pub delta: f64,
+    pub wall_delta: f64,
+}
+
+impl FrameTime {
+    pub fn shader_time(&self) -> f32 {
+        (self.elapsed % SHADER_TIME_PERIOD) as f32
+    }
+}
+
+// Time only accumulates scaled frame deltas, so pausing freezes animations where they are
+pub struct Clock {
+    previous_instant: Instant,
+    offset: f64,
+    elapsed: f64,
+    speed: f64,
+    paused: bool,
+    background_paused: bool,
+}
+
+impl Clock {
+    pub fn new(offset: f64) -> Self {
+        Self {
+            previous_instant: Instant::now(),
+            offset,
+            elapsed: offset,
+            speed: 1.0,
+            paused: false,
+            background_paused: false,
+        }
+    }
+
+    pub fn set_background_paused(&mut self, paused: bool) {
+        self.background_paused = paused;
+    }
+
+    pub fn is_background_paused(&self) -> bool {
+        self.background_paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    // Only the animation time restarts, pausing and speed are left as they are
+    pub fn reset(&mut self) {
+        self.elapsed = self.offset;
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    pub fn tick(&mut self) -> FrameTime {
+        self.tick_at(Instant::now())
+    }
+
+    fn tick_at(&mut self, now: Instant) -> FrameTime {
+        let wall_delta = now.duration_since(self.previous_instant).as_secs_f64();
+        let delta = if self.paused || self.background_paused {
+            0.0
+        } else {
+            wall_delta * self.speed
+        };
+        self.previous_instant = now;
+        self.elapsed += delta;
+
+        FrameTime {
+            elapsed: self.elapsed,
+            delta,
+            wall_delta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    const FRAME: Duration = Duration::from_micros(16_667);
+
+    #[test]
+    fn delta_stays_precise_at_large_offsets() {
+        // About 28 hours of uptime
+        let mut clock = Clock::new(100_000.0);
+        let start = clock.previous_instant;
+
+        let first = clock.tick_at(start + FRAME);
+        let second = clock.tick_at(start + FRAME * 2);
+
+        assert!((second.delta - FRAME.as_secs_f64()).abs() < 1e-9);
+        assert!((second.elapsed - first.elapsed - FRAME.as_secs_f64()).abs() < 1e-9);
+
+        let shader_delta = f64::from(second.shader_time() - first.shader_time());
+        assert!((shader_delta - FRAME.as_secs_f64()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn paused_ticks_freeze_the_animation() {
+        let mut clock = Clock::new(0.0);
+        let start = clock.previous_instant;
+        let before = clock.tick_at(start + FRAME);
+
+        clock.pause();
+        let paused = clock.tick_at(start + FRAME * 2);
+        assert_eq!(paused.delta, 0.0);
+        assert_eq!(paused.elapsed, before.elapsed);
+        assert!((paused.wall_delta - FRAME.as_secs_f64()).abs() < 1e-9);
+
+        // Resuming doesn't catch up on the paused time
+        clock.resume();
+        let resumed = clock.tick_at(start + FRAME * 3);
+        assert!((resumed.delta - FRAME.as_secs_f64()).abs() < 1e-9);
+        assert!((resumed.elapsed - before.elapsed - FRAME.as_secs_f64()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn deltas_are_scaled_by_the_speed() {
+        let mut clock = Clock::new(0.0);
+        let start = clock.previous_instant;
+
+        clock.set_speed(2.0);
+        let fast = clock.tick_at(start + FRAME);
+        assert!((fast.delta - 2.0 * FRAME.as_secs_f64()).abs() < 1e-9);
+        assert!((fast.wall_delta - FRAME.as_secs_f64()).abs() < 1e-9);
+
+        clock.set_speed(0.5);
+        let slow = clock.tick_at(start + FRAME * 2);
+        assert!((slow.delta - 0.5 * FRAME.as_secs_f64()).abs() < 1e-9);
+        assert!((slow.elapsed - 2.5 * FRAME.as_secs_f64()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn speed_is_clamped() {
+        let mut clock = Clock::new(0.0);
+
+        clock.set_speed(100.0);
+        assert_eq!(clock.speed(), 16.0);
+
+        clock.set_speed(0.0);
+        assert_eq!(clock.speed(), 1.0 / 16.0);
+
+        clock.set_speed(-1.0);
+        assert_eq!(clock.speed(), 1.0 / 16.0);
+    }
+
+    #[test]
+    fn reset_restarts_from_the_offset() {
+        let mut clock = Clock::new(5.0);
+        let start = clock.previous_instant;
+        clock.set_speed(2.0);
+        clock.tick_at(start + FRAME);
+
+        clock.reset();
+        let frame = clock.tick_at(start + FRAME * 2);
+        assert!((frame.elapsed - 5.0 - 2.0 * FRAME.as_secs_f64()).abs() < 1e-9);
+        assert_eq!(clock.speed(), 2.0);
+    }
+}
@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+use vulkano::format::Format;
+
+// Colors as they are authored: hex codes and color pickers are sRGB encoded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SrgbColor {
+    rgba: [f32; 4],
+}
+
+// Colors as the shaders light and blend them. Textures are decoded to linear by their sRGB
+// formats, so everything fed to the shaders must be linear too
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearColor {
+    rgba: [f32; 4],
+}
+
+impl SrgbColor {
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { rgba: [r, g, b, a] }
+    }
+
+    // sRGB values outside of 0..1 have no meaning, they are clamped. Alpha is never encoded
+    pub fn to_linear(self) -> LinearColor {
+        let [r, g, b, a] = self.rgba.map(|c| c.clamp(0.0, 1.0));
+        LinearColor::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        self.rgba
+    }
+}
+
+impl LinearColor {
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { rgba: [r, g, b, a] }
+    }
+
+    // Linear values above 1 are valid light intensities, but can't be displayed in sRGB
+    pub fn to_srgb(self) -> SrgbColor {
+        let [r, g, b, a] = self.rgba.map(|c| c.clamp(0.0, 1.0));
+        SrgbColor::new(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        self.rgba
+    }
+}
+
+impl FromStr for SrgbColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let channel = |i: usize| {
+            hex.get(2 * i..2 * i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .map(|value| value as f32 / 255.0)
+        };
+
+        let rgba = match hex.len() {
+            _ if !hex.bytes().all(|b| b.is_ascii_hexdigit()) => [None; 4],
+            6 => [channel(0), channel(1), channel(2), Some(1.0)],
+            8 => [channel(0), channel(1), channel(2), channel(3)],
+            _ => [None; 4],
+        };
+
+        match rgba {
+            [Some(r), Some(g), Some(b), Some(a)] => Ok(SrgbColor::new(r, g, b, a)),
+            _ => Err(format!("expected a hex color like #1a2b3c, got {s}")),
+        }
+    }
+}
+
+pub fn is_srgb_format(format: Format) -> bool {
+    matches!(format, Format::B8G8R8A8Srgb | Format::R8G8B8A8Srgb)
+}
+
+// Clear values are written to the attachment like shader output: an sRGB attachment encodes them,
+// a UNORM one stores them as is, so they must be encoded beforehand
+pub fn attachment_color(color: SrgbColor, format: Format) -> [f32; 4] {
+    if is_srgb_format(format) {
+        color.to_linear().to_array()
+    } else {
+        color.to_array()
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn conversions_match_reference_values() {
+        let linear = SrgbColor::new(0.5, 0.04045, 1.0, 0.5).to_linear();
+        assert_close(linear.to_array(), [0.214_04, 0.003_130_8, 1.0, 0.5]);
+
+        let srgb = LinearColor::new(0.5, 0.001, 0.0, 0.25).to_srgb();
+        assert_close(srgb.to_array(), [0.735_36, 0.012_92, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        for i in 0..=255 {
+            let c = i as f32 / 255.0;
+            let srgb = SrgbColor::new(c, c, c, 1.0);
+            assert_close(srgb.to_linear().to_srgb().to_array(), srgb.to_array());
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        let srgb = LinearColor::new(4.0, -1.0, 1.0, 1.0).to_srgb();
+        assert_close(srgb.to_array(), [1.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn hex_colors_are_parsed() {
+        assert_eq!(
+            "#ff8000".parse::<SrgbColor>().unwrap(),
+            SrgbColor::new(1.0, 128.0 / 255.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            "00000080".parse::<SrgbColor>().unwrap(),
+            SrgbColor::new(0.0, 0.0, 0.0, 128.0 / 255.0)
+        );
+
+        for invalid in [
+            "",
+            "#fff",
+            "#ff800",
+            "#gg0000",
+            "#+f+f+f",
+            "#ff8000ff00",
+            "#ff80é",
+        ] {
+            assert!(
+                invalid.parse::<SrgbColor>().is_err(),
+                "{invalid} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn clear_color_is_encoded_for_unorm_attachments() {
+        let grey = SrgbColor::new(0.5, 0.5, 0.5, 1.0);
+
+        assert_close(
+            attachment_color(grey, Format::B8G8R8A8Srgb),
+            [0.214_04, 0.214_04, 0.214_04, 1.0],
+        );
+        assert_eq!(
+            attachment_color(grey, Format::B8G8R8A8Unorm),
+            [0.5, 0.5, 0.5, 1.0]
+        );
+    }
+}
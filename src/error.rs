@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use vulkano::{
+    device::DeviceCreationError,
+    framebuffer::{FramebufferCreationError, RenderPassCreationError},
+    image::{view::ImageViewCreationError, ImageCreationError},
+    instance::{debug::DebugCallbackCreationError, InstanceCreationError},
+    memory::DeviceMemoryAllocError,
+    pipeline::GraphicsPipelineCreationError,
+    sampler::SamplerCreationError,
+    swapchain::{CapabilitiesError, SwapchainCreationError},
+    sync::FlushError,
+    OomError,
+};
+
+use thiserror::Error;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(
+        "couldn't find a suitable physical device, available devices:{}",
+        format_lines(.devices)
+    )]
+    NoSuitableDevice { devices: Vec<String> },
+
+    #[error("couldn't find a supported depth format")]
+    NoSuitableDepthFormat,
+
+    #[error(
+        "asset not found: {}, attempted locations:{}{}",
+        .path.display(),
+        format_paths(.attempted),
+        format_near_misses(.near_misses)
+    )]
+    AssetNotFound {
+        path: PathBuf,
+        attempted: Vec<PathBuf>,
+        near_misses: Vec<String>,
+    },
+
+    #[error(
+        "asset {} is in archive {}, a loose file is needed",
+        .path.display(),
+        .archive.display()
+    )]
+    AssetInArchive { path: PathBuf, archive: PathBuf },
+
+    #[error("failed to read asset archive")]
+    Archive(#[from] zip::result::ZipError),
+
+    #[error("failed to open asset archive {}", .path.display())]
+    ArchiveMount {
+        path: PathBuf,
+        source: zip::result::ZipError,
+    },
+
+    #[cfg(feature = "hot-reload")]
+    #[error("couldn't initialize the shader compiler")]
+    ShaderCompilerUnavailable,
+
+    #[cfg(feature = "hot-reload")]
+    #[error("failed to compile shader {}:\n{log}", .file.display())]
+    ShaderCompile { file: PathBuf, log: String },
+
+    #[error("failed to read file")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to create instance")]
+    InstanceCreation(#[from] InstanceCreationError),
+
+    #[error("failed to create debug callback")]
+    DebugCallbackCreation(#[from] DebugCallbackCreationError),
+
+    #[error("failed to create surface")]
+    SurfaceCreation(#[from] vulkano_win::CreationError),
+
+    #[error("failed to create device")]
+    DeviceCreation(#[from] DeviceCreationError),
+
+    #[error("failed to query surface capabilities")]
+    SurfaceCapabilities(#[from] CapabilitiesError),
+
+    #[error("failed to create swapchain")]
+    SwapchainCreation(#[from] SwapchainCreationError),
+
+    #[error("failed to load model")]
+    ModelLoad(#[from] tobj::LoadError),
+
+    #[error("failed to decode image")]
+    ImageDecode(#[from] image::ImageError),
+
+    #[error("failed to allocate device memory")]
+    MemoryAllocation(#[from] DeviceMemoryAllocError),
+
+    #[error("failed to create image")]
+    ImageCreation(#[from] ImageCreationError),
+
+    #[error("failed to create image view")]
+    ImageViewCreation(#[from] ImageViewCreationError),
+
+    #[error("failed to create sampler")]
+    SamplerCreation(#[from] SamplerCreationError),
+
+    #[error("failed to create render pass")]
+    RenderPassCreation(#[from] RenderPassCreationError),
+
+    #[error("failed to create graphics pipeline")]
+    PipelineCreation(#[from] GraphicsPipelineCreationError),
+
+    #[error("failed to create framebuffer")]
+    FramebufferCreation(#[from] FramebufferCreationError),
+
+    #[error("failed to flush commands")]
+    Flush(#[from] FlushError),
+
+    #[error("out of memory")]
+    OutOfMemory(#[from] OomError),
+}
+
+fn format_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("\n  {}", path.display()))
+        .collect()
+}
+
+fn format_lines(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("\n  {line}")).collect()
+}
+
+fn format_near_misses(near_misses: &[String]) -> String {
+    if near_misses.is_empty() {
+        String::new()
+    } else {
+        format!("\nsimilar archive entries:{}", format_lines(near_misses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_suitable_device_lists_devices() {
+        let error = Error::NoSuitableDevice {
+            devices: vec![
+                "0: llvmpipe (Cpu)".to_owned(),
+                "1: Radeon (DiscreteGpu)".to_owned(),
+            ],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "couldn't find a suitable physical device, available devices:\n  0: llvmpipe (Cpu)\n  \
+             1: Radeon (DiscreteGpu)"
+        );
+    }
+
+    #[test]
+    fn asset_not_found_lists_attempted_locations() {
+        let error = Error::AssetNotFound {
+            path: PathBuf::from("models/missing.obj"),
+            attempted: vec![
+                PathBuf::from("/opt/demo/models/missing.obj"),
+                PathBuf::from("/home/user/models/missing.obj"),
+            ],
+            near_misses: Vec::new(),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "asset not found: models/missing.obj, attempted locations:\n  \
+             /opt/demo/models/missing.obj\n  /home/user/models/missing.obj"
+        );
+    }
+
+    #[test]
+    fn asset_not_found_lists_near_misses() {
+        let error = Error::AssetNotFound {
+            path: PathBuf::from("models/cube.obj"),
+            attempted: vec![PathBuf::from("assets.zip/models/cube.obj")],
+            near_misses: vec!["models/Cube.obj".to_owned()],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "asset not found: models/cube.obj, attempted locations:\n  assets.zip/models/cube.obj\n\
+             similar archive entries:\n  models/Cube.obj"
+        );
+    }
+}
@@ -1,53 +1,57 @@
-use crate::init::{create_framebuffers, update_dynamic_viewport};
-use crate::lib::*;
-
-use std::{sync::Arc, time::Instant};
-
-use vulkano::{
-    buffer::CpuBufferPool,
-    command_buffer::{AutoCommandBufferBuilder, DynamicState, SubpassContents},
-    descriptor::{descriptor_set::FixedSizeDescriptorSetsPool, DescriptorSet},
-    device::Queue,
-    format::Format,
-    framebuffer::{FramebufferAbstract, RenderPassAbstract},
-    image::{view::ImageView, ImmutableImage},
-    pipeline::GraphicsPipelineAbstract,
-    sampler::Sampler,
-    swapchain::{self, AcquireError, Swapchain, SwapchainCreationError},
-    sync::{self, FlushError, GpuFuture},
+use vulkan_rust_tutorial::{
+    audio::AudioSystem,
+    camera::Camera,
+    clock::Clock,
+    focus::FocusState,
+    init::validation_error_count,
+    renderer::{FrameInput, Renderer},
+    stats::FrameStats,
+    turntable::Turntable,
+    TITLE,
 };
+
 use winit::{
-    event::{Event, VirtualKeyCode, WindowEvent},
+    event::{ElementState, Event, ModifiersState, VirtualKeyCode, WindowEvent},
     event_loop::ControlFlow,
     window::Window,
 };
 
-use nalgebra_glm as glm;
-
-use color_eyre::{eyre::eyre, Result};
+use log::{info, warn};
+
+use color_eyre::Result;
+
+// Everything the binary drives around the renderer: input, animation, pacing and sounds
+pub struct HostState {
+    pub audio: AudioSystem,
+    pub camera: Camera,
+    pub turntable: Turntable,
+    pub clock: Clock,
+    pub focus: FocusState,
+    pub stats: Option<FrameStats>,
+    // The last stats title, restored when the UV test flag is removed from it
+    pub title: String,
+    pub modifiers: ModifiersState,
+}
 
-#[allow(clippy::too_many_arguments)]
 pub fn main_loop(
     event: Event<()>,
     control_flow: &mut ControlFlow,
-    start_instant: Instant,
-    graphics_queue: Arc<Queue>,
-    present_queue: Arc<Queue>,
-    vertex_buffer: VertexBuffer,
-    index_buffer: IndexBuffer,
-    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
-    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
-    texture: Arc<ImmutableImage<Format>>,
-    sampler: Arc<Sampler>,
-    uniform_buffer: &CpuBufferPool<vs::ty::UniformBufferObject>,
-    descriptor_pool: &mut FixedSizeDescriptorSetsPool,
-    swapchain: &mut Arc<Swapchain<Window>>,
-    dynamic_state: &mut DynamicState,
-    framebuffers: &mut Vec<Arc<dyn FramebufferAbstract + Send + Sync>>,
-    swapchain_out_of_date: &mut bool,
-    previous_frame_future: &mut Option<Box<dyn GpuFuture>>,
+    window: &Window,
+    renderer: &mut Renderer<Window>,
+    host: &mut HostState,
 ) -> Result<()> {
     //
+    let HostState {
+        audio,
+        camera,
+        turntable,
+        clock,
+        focus,
+        stats,
+        title,
+        modifiers,
+    } = host;
+
     match event {
         Event::WindowEvent { event, .. } => match event {
             WindowEvent::CloseRequested => {
@@ -58,169 +62,184 @@ pub fn main_loop(
             {
                 *control_flow = ControlFlow::Exit;
             }
-            WindowEvent::Resized(_) => {
-                *swapchain_out_of_date = true;
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::L) =>
+            {
+                renderer.cycle_letterbox();
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::C) =>
+            {
+                renderer.toggle_culling();
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::Tab) =>
+            {
+                renderer.toggle_wireframe();
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::Space) =>
+            {
+                if clock.is_paused() {
+                    clock.resume();
+                    audio.play("resume.ogg");
+                    info!("Animation resumed");
+                } else {
+                    clock.pause();
+                    audio.play("pause.ogg");
+                    info!("Animation paused");
+                }
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::R) =>
+            {
+                clock.reset();
+                turntable.reset();
+                info!("Animation reset");
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && matches!(
+                        input.virtual_keycode,
+                        Some(
+                            VirtualKeyCode::Equals
+                                | VirtualKeyCode::Plus
+                                | VirtualKeyCode::NumpadAdd
+                        )
+                    ) =>
+            {
+                clock.set_speed(clock.speed() * 2.0);
+                info!("Animation speed: {}x", clock.speed());
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && matches!(
+                        input.virtual_keycode,
+                        Some(VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract)
+                    ) =>
+            {
+                clock.set_speed(clock.speed() / 2.0);
+                info!("Animation speed: {}x", clock.speed());
+            }
+            WindowEvent::ModifiersChanged(state) => {
+                *modifiers = state;
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && modifiers.shift()
+                    && input.virtual_keycode == Some(VirtualKeyCode::T) =>
+            {
+                renderer.toggle_uv_test();
+                set_title(window, title, renderer.uv_test_active());
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::F12) =>
+            {
+                let accepted = renderer.request_screenshot();
+                if accepted {
+                    audio.play("screenshot.ogg");
+                }
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::V) =>
+            {
+                renderer.cycle_present_mode();
+                audio.play("toggle.ogg");
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == ElementState::Pressed
+                    && input.virtual_keycode == Some(VirtualKeyCode::M) =>
+            {
+                audio.toggle_mute();
+            }
+            WindowEvent::Resized(size) => {
+                renderer.resize(size.into());
+            }
+            WindowEvent::Focused(focused) => {
+                if !focused {
+                    camera.release_drag();
+                }
+                if focus.set_focused(focused, clock) {
+                    if let Some(stats) = stats {
+                        stats.reset();
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                camera.mouse_input(state, button);
+                turntable.interrupt();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let dragged = camera.cursor_moved(position);
+                if dragged {
+                    turntable.interrupt();
+                }
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                camera.mouse_wheel(delta);
+                turntable.interrupt();
             }
             _ => (),
         },
 
-        Event::RedrawEventsCleared => {
-            if let Some(future) = previous_frame_future {
-                future.cleanup_finished();
+        // Frames are paced here, redraws requested by the OS (e.g. on expose) are always honored
+        Event::MainEventsCleared => {
+            if renderer.is_suspended() {
+                // Resized wakes the loop up again once the window is restored
+                *control_flow = ControlFlow::Wait;
+                if let Some(stats) = stats {
+                    stats.reset();
+                }
+            } else if focus.should_render(control_flow) {
+                window.request_redraw();
             }
+        }
 
-            let (image_num, suboptimal, acquire_future) =
-                match swapchain::acquire_next_image(swapchain.clone(), None) {
-                    Ok(r) => r,
-                    Err(AcquireError::OutOfDate) => {
-                        return recreate_swapchain(
-                            swapchain,
-                            render_pass.clone(),
-                            dynamic_state,
-                            framebuffers,
-                            swapchain_out_of_date,
-                        );
-                    }
-                    Err(e) => return Err(eyre!("Failed to acquire next image: {e:?}")),
-                };
+        Event::LoopDestroyed => {
+            audio.shutdown();
 
-            if suboptimal {
-                *swapchain_out_of_date = true;
+            let validation_errors = validation_error_count();
+            if validation_errors > 0 {
+                warn!("{validation_errors} validation errors were reported");
             }
+        }
 
-            let set = update_descriptor_set(
-                start_instant,
-                uniform_buffer,
-                descriptor_pool,
-                texture,
-                sampler,
-            )?;
-
-            let mut builder = AutoCommandBufferBuilder::primary_one_time_submit(
-                pipeline.device().clone(),
-                graphics_queue.family(),
+        Event::RedrawRequested(_) => {
+            let frame_time = clock.tick();
+            turntable.update(frame_time.delta, frame_time.wall_delta);
+            renderer.render_frame(
+                frame_time,
+                FrameInput {
+                    view: camera.view_matrix(),
+                    model: turntable.transform(),
+                },
             )?;
 
-            builder
-                .begin_render_pass(
-                    framebuffers[image_num].clone(),
-                    SubpassContents::Inline,
-                    vec![[0.0, 0.0, 0.0, 1.0].into(), 1.0.into()],
-                )?
-                .draw_indexed(
-                    pipeline.clone(),
-                    dynamic_state,
-                    vec![vertex_buffer],
-                    index_buffer,
-                    set,
-                    (),
-                    vec![],
-                )?
-                .end_render_pass()?;
-
-            let command_buffer = builder.build()?;
-
-            match previous_frame_future
-                .take()
-                .unwrap_or_else(|| Box::new(sync::now(pipeline.device().clone())))
-                .join(acquire_future)
-                .then_execute(graphics_queue, command_buffer)?
-                .then_swapchain_present(present_queue, swapchain.clone(), image_num)
-                .then_signal_fence_and_flush()
-            {
-                Ok(future) => {
-                    *previous_frame_future = Some(Box::new(future));
-                }
-                Err(FlushError::OutOfDate) => {
-                    *swapchain_out_of_date = true;
-                    *previous_frame_future = None;
-                }
-                Err(e) => {
-                    println!("Failed to flush future: {e:?}");
-                    *previous_frame_future = None;
+            if let Some(stats) = stats {
+                stats.record_frame();
+                if let Some(summary) = stats.title_update_due() {
+                    *title = summary.title(TITLE);
+                    set_title(window, title, renderer.uv_test_active());
                 }
             }
-
-            if *swapchain_out_of_date {
-                recreate_swapchain(
-                    swapchain,
-                    render_pass.clone(),
-                    dynamic_state,
-                    framebuffers,
-                    swapchain_out_of_date,
-                )?;
-            }
         }
         _ => (),
     }
     Ok(())
 }
 
-fn update_descriptor_set(
-    start_instant: Instant,
-    uniform_buffer: &CpuBufferPool<vs::ty::UniformBufferObject>,
-    descriptor_pool: &mut FixedSizeDescriptorSetsPool,
-    texture: Arc<ImmutableImage<Format>>,
-    sampler: Arc<Sampler>,
-) -> Result<Arc<dyn DescriptorSet + Send + Sync>> {
-    //
-    let elapsed = start_instant.elapsed().as_nanos() as f32 / 1_000_000_000.0;
-
-    let mut ubo = vs::ty::UniformBufferObject {
-        model: glm::rotate(
-            &glm::identity(),
-            elapsed * f32::to_radians(90.0),
-            &glm::vec3(0.0, 0.0, 1.0),
-        )
-        .into(),
-
-        view: glm::look_at(
-            &glm::vec3(2.0, 2.0, 2.0),
-            &glm::vec3(0.0, 0.0, 0.0),
-            &glm::vec3(0.0, 0.0, 1.0),
-        )
-        .into(),
-
-        proj: glm::perspective(
-            WIDTH as f32 / HEIGHT as f32,
-            f32::to_radians(45.0),
-            0.1,
-            10.0,
-        )
-        .into(),
-    };
-    ubo.proj[1][1] *= -1.0;
-
-    Ok(Arc::new(
-        descriptor_pool
-            .next()
-            .add_buffer(uniform_buffer.next(ubo)?)?
-            .add_sampled_image(ImageView::new(texture)?, sampler)?
-            .build()?,
-    ))
-}
-
-fn recreate_swapchain(
-    swapchain: &mut Arc<Swapchain<Window>>,
-    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
-    dynamic_state: &mut DynamicState,
-    framebuffers: &mut Vec<Arc<dyn FramebufferAbstract + Send + Sync>>,
-    swapchain_out_of_date: &mut bool,
-) -> Result<()> {
-    //
-    let (new_swapchain, new_swapchain_images) = match swapchain
-        .recreate_with_dimensions(swapchain.surface().window().inner_size().into())
-    {
-        Ok(r) => r,
-        Err(SwapchainCreationError::UnsupportedDimensions) => return Ok(()),
-        Err(e) => return Err(eyre!("Failed to recreate swapchain: {e:?}")),
-    };
-    *swapchain = new_swapchain;
-
-    update_dynamic_viewport(swapchain.clone(), dynamic_state);
-
-    *framebuffers = create_framebuffers(new_swapchain_images, render_pass)?;
-
-    *swapchain_out_of_date = false;
-    Ok(())
+// Flagged in the title so screenshots of the test pattern aren't mistaken for real content
+fn set_title(window: &Window, title: &str, uv_test_active: bool) {
+    if uv_test_active {
+        window.set_title(&format!("{title} [UV test pattern]"));
+    } else {
+        window.set_title(title);
+    }
 }
@@ -0,0 +1,174 @@
+use crate::clock::Clock;
+
+use std::time::{Duration, Instant};
+
+use winit::event_loop::ControlFlow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Foreground,
+    Background,
+}
+
+pub struct FocusState {
+    focus: Focus,
+    background_fps: f64,
+    pause_in_background: bool,
+    next_background_frame: Instant,
+}
+
+impl FocusState {
+    pub fn new(background_fps: f64, pause_in_background: bool) -> Self {
+        Self {
+            focus: Focus::Foreground,
+            background_fps,
+            pause_in_background,
+            next_background_frame: Instant::now(),
+        }
+    }
+
+    // Returns whether the frame cap changed
+    pub fn set_focused(&mut self, focused: bool, clock: &mut Clock) -> bool {
+        let focus = if focused {
+            Focus::Foreground
+        } else {
+            Focus::Background
+        };
+
+        match (self.focus, focus) {
+            (Focus::Foreground, Focus::Background) => {
+                if self.pause_in_background {
+                    clock.set_background_paused(true);
+                }
+                self.next_background_frame = Instant::now();
+            }
+            (Focus::Background, Focus::Foreground) if self.pause_in_background => {
+                clock.set_background_paused(false);
+            }
+            _ => (),
+        }
+
+        let changed = focus != self.focus;
+        self.focus = focus;
+        changed
+    }
+
+    pub fn should_render(&mut self, control_flow: &mut ControlFlow) -> bool {
+        self.should_render_at(control_flow, Instant::now())
+    }
+
+    fn should_render_at(&mut self, control_flow: &mut ControlFlow, now: Instant) -> bool {
+        if *control_flow == ControlFlow::Exit {
+            return false;
+        }
+
+        match self.focus {
+            Focus::Foreground => {
+                *control_flow = ControlFlow::Poll;
+                true
+            }
+            Focus::Background if self.background_fps <= 0.0 => {
+                *control_flow = ControlFlow::Wait;
+                false
+            }
+            Focus::Background => {
+                if now < self.next_background_frame {
+                    *control_flow = ControlFlow::WaitUntil(self.next_background_frame);
+                    return false;
+                }
+
+                self.next_background_frame =
+                    now + Duration::from_secs_f64(1.0 / self.background_fps);
+                *control_flow = ControlFlow::WaitUntil(self.next_background_frame);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foreground_renders_continuously() {
+        let mut focus = FocusState::new(10.0, false);
+        let mut control_flow = ControlFlow::Wait;
+
+        assert!(focus.should_render(&mut control_flow));
+        assert_eq!(control_flow, ControlFlow::Poll);
+    }
+
+    #[test]
+    fn exit_is_never_overridden() {
+        let mut focus = FocusState::new(10.0, false);
+        let mut control_flow = ControlFlow::Exit;
+
+        assert!(!focus.should_render(&mut control_flow));
+        assert_eq!(control_flow, ControlFlow::Exit);
+    }
+
+    #[test]
+    fn unfocusing_pauses_the_clock_only_when_configured() {
+        let mut clock = Clock::new(0.0);
+
+        let mut focus = FocusState::new(10.0, false);
+        focus.set_focused(false, &mut clock);
+        assert!(!clock.is_background_paused());
+
+        let mut focus = FocusState::new(10.0, true);
+        focus.set_focused(false, &mut clock);
+        assert!(clock.is_background_paused());
+        assert_eq!(clock.tick().delta, 0.0);
+
+        focus.set_focused(true, &mut clock);
+        assert!(!clock.is_background_paused());
+        assert_eq!(focus.focus, Focus::Foreground);
+    }
+
+    #[test]
+    fn repeated_focus_events_keep_the_state() {
+        let mut clock = Clock::new(0.0);
+        let mut focus = FocusState::new(10.0, true);
+
+        assert!(!focus.set_focused(true, &mut clock));
+        assert_eq!(focus.focus, Focus::Foreground);
+        assert!(!clock.is_background_paused());
+
+        assert!(focus.set_focused(false, &mut clock));
+        assert!(!focus.set_focused(false, &mut clock));
+        assert_eq!(focus.focus, Focus::Background);
+        assert!(clock.is_background_paused());
+    }
+
+    #[test]
+    fn background_frames_are_capped() {
+        let mut clock = Clock::new(0.0);
+        let mut focus = FocusState::new(10.0, false);
+        let mut control_flow = ControlFlow::Poll;
+
+        focus.set_focused(false, &mut clock);
+        let start = Instant::now();
+        let next_frame = start + Duration::from_millis(100);
+
+        assert!(focus.should_render_at(&mut control_flow, start));
+        assert_eq!(control_flow, ControlFlow::WaitUntil(next_frame));
+
+        assert!(!focus.should_render_at(&mut control_flow, start + Duration::from_millis(50)));
+        assert_eq!(control_flow, ControlFlow::WaitUntil(next_frame));
+
+        assert!(focus.should_render_at(&mut control_flow, next_frame));
+    }
+
+    #[test]
+    fn zero_background_fps_stops_rendering() {
+        let mut clock = Clock::new(0.0);
+        let mut focus = FocusState::new(0.0, false);
+        let mut control_flow = ControlFlow::Poll;
+
+        focus.set_focused(false, &mut clock);
+
+        assert!(!focus.should_render(&mut control_flow));
+        assert_eq!(control_flow, ControlFlow::Wait);
+    }
+}
@@ -1,6 +1,20 @@
-use crate::lib::*;
+use crate::assets::{builtin, AssetRoot};
+use crate::error::{Error, Result};
+use crate::letterbox::Letterbox;
+use crate::mesh::{
+    correction_transform, deduplicate_vertices, guess_unit_scale, guess_up_axis, Aabb, UpAxis,
+};
+use crate::pipeline::PipelineOptions;
+use crate::presentation::{
+    Latency, PresentModePreference, PresentationLimits, PresentationProfile,
+};
+use crate::shaders::Shaders;
+use crate::{fs, vs, IndexBuffer, MeshBuffers, Vertex, HEIGHT, TITLE, WIDTH};
 
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use vulkano::{
     buffer::{BufferUsage, ImmutableBuffer},
@@ -14,13 +28,18 @@ use vulkano::{
     },
     instance::{
         debug::{DebugCallback, MessageSeverity, MessageType},
-        ApplicationInfo, Instance, PhysicalDevice, QueueFamily, Version,
+        layers_list, ApplicationInfo, Instance, PhysicalDevice, PhysicalDeviceType, QueueFamily,
+        Version,
+    },
+    pipeline::{
+        raster::{CullMode, FrontFace, PolygonMode},
+        shader::GraphicsEntryPoint,
+        viewport::Viewport,
+        GraphicsPipeline, GraphicsPipelineAbstract,
     },
-    pipeline::{viewport::Viewport, GraphicsPipeline, GraphicsPipelineAbstract},
     sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode},
     swapchain::{
-        ColorSpace, CompositeAlpha, FullscreenExclusive, PresentMode, Surface, SurfaceTransform,
-        Swapchain,
+        ColorSpace, CompositeAlpha, FullscreenExclusive, Surface, SurfaceTransform, Swapchain,
     },
     sync::{GpuFuture, SharingMode},
 };
@@ -31,11 +50,24 @@ use winit::{
     window::{Window, WindowBuilder},
 };
 
-use image::GenericImageView;
+use nalgebra_glm as glm;
+
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+
+use log::{debug, error, info, trace, warn, LevelFilter};
+
+static VALIDATION_ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn validation_error_count() -> usize {
+    VALIDATION_ERRORS.load(Ordering::Relaxed)
+}
 
-use color_eyre::{eyre::eyre, Result};
+const VALIDATION_LAYERS: [&str; 2] = [
+    "VK_LAYER_KHRONOS_validation",
+    "VK_LAYER_LUNARG_standard_validation",
+];
 
-pub fn create_instance() -> Result<Arc<Instance>> {
+pub fn create_instance(validation: bool) -> Result<(Arc<Instance>, bool)> {
     let version = Version {
         major: 1,
         minor: 0,
@@ -44,44 +76,73 @@ pub fn create_instance() -> Result<Arc<Instance>> {
 
     let mut required_extensions = vulkano_win::required_extensions();
     let mut layers = Vec::new();
-    if cfg!(debug_assertions) {
+
+    let validation_layer = if validation {
+        find_validation_layer()
+    } else {
+        None
+    };
+    if let Some(layer) = validation_layer {
+        info!("Using validation layer {layer}");
         required_extensions.ext_debug_utils = true;
-        layers.push("VK_LAYER_LUNARG_standard_validation");
+        layers.push(layer);
     }
 
-    Ok(Instance::new(
+    let instance = Instance::new(
         Some(&ApplicationInfo {
-            application_name: Some("Vulkan Application".into()),
+            application_name: Some(TITLE.into()),
             application_version: Some(version),
             engine_name: Some("No Engine".into()),
             engine_version: Some(version),
         }),
         &required_extensions,
         layers,
-    )?)
+    )?;
+
+    Ok((instance, validation_layer.is_some()))
+}
+
+fn find_validation_layer() -> Option<&'static str> {
+    let available_layers: Vec<_> = match layers_list() {
+        Ok(layers) => layers.map(|layer| layer.name().to_owned()).collect(),
+        Err(e) => {
+            warn!("Failed to list instance layers, validation disabled: {e:?}");
+            return None;
+        }
+    };
+
+    let layer = VALIDATION_LAYERS
+        .iter()
+        .find(|&&name| available_layers.iter().any(|layer| layer == name))
+        .copied();
+
+    if layer.is_none() {
+        warn!("No validation layer installed (tried {VALIDATION_LAYERS:?}), validation disabled");
+    }
+    layer
 }
 
-pub fn create_debug_callback(instance: &Arc<Instance>) -> Result<Option<DebugCallback>> {
-    if cfg!(debug_assertions) {
+pub fn create_debug_callback(
+    instance: &Arc<Instance>,
+    validation_enabled: bool,
+) -> Result<Option<DebugCallback>> {
+    //
+    if validation_enabled {
         Ok(Some(DebugCallback::new(
             instance,
-            MessageSeverity::errors_and_warnings(),
+            message_severity(log::max_level()),
             MessageType::all(),
             |msg| {
-                let message_severity = if msg.severity.error {
-                    "error"
-                } else if msg.severity.warning {
-                    "warning"
-                } else if msg.severity.information {
-                    "information"
-                } else if msg.severity.verbose {
-                    "verbose"
+                let message_type = if msg.ty.validation {
+                    "validation"
+                } else if msg.ty.performance {
+                    "performance"
                 } else {
-                    unimplemented!()
+                    "general"
                 };
+                let message_id = msg.layer_prefix.unwrap_or("unknown");
 
-                let message_description = msg.description;
-                println!("validation layer: (severity: {message_severity}) {message_description}");
+                log_message(msg.severity, message_type, message_id, msg.description);
             },
         )?))
     } else {
@@ -89,6 +150,35 @@ pub fn create_debug_callback(instance: &Arc<Instance>) -> Result<Option<DebugCal
     }
 }
 
+// Info and verbose messages are only requested when they would be logged, the layer emits a lot
+// of them and formatting them is not free
+fn message_severity(max_level: LevelFilter) -> MessageSeverity {
+    MessageSeverity {
+        information: max_level >= LevelFilter::Debug,
+        verbose: max_level >= LevelFilter::Trace,
+        ..MessageSeverity::errors_and_warnings()
+    }
+}
+
+fn log_message(
+    severity: MessageSeverity,
+    message_type: &str,
+    message_id: &str,
+    message_description: &str,
+) {
+    //
+    if severity.error {
+        VALIDATION_ERRORS.fetch_add(1, Ordering::Relaxed);
+        error!("[{message_type}] {message_id}: {message_description}");
+    } else if severity.warning {
+        warn!("[{message_type}] {message_id}: {message_description}");
+    } else if severity.information {
+        debug!("[{message_type}] {message_id}: {message_description}");
+    } else {
+        trace!("[{message_type}] {message_id}: {message_description}");
+    }
+}
+
 pub fn create_surface(instance: Arc<Instance>) -> Result<(Arc<Surface<Window>>, EventLoop<()>)> {
     let events_loop = EventLoop::new();
 
@@ -97,26 +187,118 @@ pub fn create_surface(instance: Arc<Instance>) -> Result<(Arc<Surface<Window>>,
             width: WIDTH,
             height: HEIGHT,
         })
-        .with_title("Vulkan Application")
+        .with_title(TITLE)
         .build_vk_surface(&events_loop, instance)?;
 
     Ok((surface, events_loop))
 }
 
-pub fn pick_queues_families(surface: &Arc<Surface<Window>>) -> Result<(QueueFamily, QueueFamily)> {
-    for physical_device in PhysicalDevice::enumerate(surface.instance()) {
-        let queue_families: Vec<_> = physical_device.queue_families().collect();
+pub fn pick_queues_families<'a, W>(
+    surface: &'a Arc<Surface<W>>,
+    gpu: Option<&str>,
+) -> Result<(QueueFamily<'a>, QueueFamily<'a>)> {
+    //
+    let best = PhysicalDevice::enumerate(surface.instance())
+        .filter(|&physical_device| gpu.is_none_or(|gpu| matches_gpu(physical_device, gpu)))
+        .filter(|&physical_device| is_device_suitable(surface, physical_device))
+        .filter_map(|physical_device| {
+            let families: Vec<_> = physical_device
+                .queue_families()
+                .map(|q| FamilyProperties {
+                    id: q.id(),
+                    graphics: q.supports_graphics(),
+                    present: surface.is_supported(q).unwrap_or(false),
+                })
+                .collect();
+
+            let (graphics_id, present_id) = select_families(&families)?;
+            Some((
+                physical_device.queue_family_by_id(graphics_id)?,
+                physical_device.queue_family_by_id(present_id)?,
+            ))
+        })
+        .max_by_key(|(graphics_queue_family, _)| {
+            device_type_score(graphics_queue_family.physical_device().ty())
+        });
+
+    match best {
+        Some((graphics_queue_family, present_queue_family)) => {
+            let physical_device = graphics_queue_family.physical_device();
+            info!(
+                "Using physical device {} ({:?})",
+                physical_device.name(),
+                physical_device.ty()
+            );
+            Ok((graphics_queue_family, present_queue_family))
+        }
+        None => Err(Error::NoSuitableDevice {
+            devices: PhysicalDevice::enumerate(surface.instance())
+                .map(|physical_device| {
+                    format!(
+                        "{}: {} ({:?})",
+                        physical_device.index(),
+                        physical_device.name(),
+                        physical_device.ty()
+                    )
+                })
+                .collect(),
+        }),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FamilyProperties {
+    id: u32,
+    graphics: bool,
+    present: bool,
+}
+
+// A family supporting both graphics and presentation is preferred, the swapchain images can then
+// stay exclusive to it
+fn select_families(families: &[FamilyProperties]) -> Option<(u32, u32)> {
+    if let Some(family) = families.iter().find(|f| f.graphics && f.present) {
+        return Some((family.id, family.id));
+    }
+
+    let graphics = families.iter().find(|f| f.graphics)?;
+    let present = families.iter().find(|f| f.present)?;
+    Some((graphics.id, present.id))
+}
+
+// The GPU can be selected either by its index or by a case-insensitive part of its name
+fn matches_gpu(physical_device: PhysicalDevice, gpu: &str) -> bool {
+    match gpu.parse::<usize>() {
+        Ok(index) => physical_device.index() == index,
+        Err(_) => physical_device
+            .name()
+            .to_lowercase()
+            .contains(&gpu.to_lowercase()),
+    }
+}
 
-        if let (Some(&graphics_queue_family), Some(&present_queue_family)) = (
-            queue_families.iter().find(|&&q| q.supports_graphics()),
-            queue_families
-                .iter()
-                .find(|&&q| surface.is_supported(q).unwrap_or(false)),
-        ) {
-            return Ok((graphics_queue_family, present_queue_family));
+// Must stay in sync with the extensions requested in create_device
+fn is_device_suitable<W>(surface: &Arc<Surface<W>>, physical_device: PhysicalDevice) -> bool {
+    let supports_swapchain = DeviceExtensions::supported_by_device(physical_device).khr_swapchain;
+
+    let supports_surface = match surface.capabilities(physical_device) {
+        Ok(capabilities) => {
+            !capabilities.supported_formats.is_empty()
+                && capabilities.present_modes.iter().next().is_some()
         }
+        Err(_) => false,
+    };
+
+    supports_swapchain && supports_surface
+}
+
+fn device_type_score(device_type: PhysicalDeviceType) -> u32 {
+    match device_type {
+        PhysicalDeviceType::DiscreteGpu => 4,
+        PhysicalDeviceType::IntegratedGpu => 3,
+        PhysicalDeviceType::VirtualGpu => 2,
+        PhysicalDeviceType::Cpu => 1,
+        PhysicalDeviceType::Other => 0,
     }
-    Err(eyre!("couldn't find a suitable physical device"))
 }
 
 pub fn create_device(
@@ -129,11 +311,17 @@ pub fn create_device(
         queue_families.push((present_queue_family, 1.0));
     }
 
+    // Anisotropic filtering and wireframe rendering are optional, software implementations like
+    // lavapipe may lack them
+    let physical_device = graphics_queue_family.physical_device();
+    let supported_features = physical_device.supported_features();
+
     let (device, queues) = {
         Device::new(
-            graphics_queue_family.physical_device(),
+            physical_device,
             &Features {
-                sampler_anisotropy: true,
+                sampler_anisotropy: supported_features.sampler_anisotropy,
+                fill_mode_non_solid: supported_features.fill_mode_non_solid,
                 ..Features::none()
             },
             &DeviceExtensions {
@@ -160,31 +348,44 @@ pub fn create_device(
     Ok((device, graphics_queue, present_queue))
 }
 
-#[allow(clippy::type_complexity)]
-pub fn create_swapchain(
-    surface: Arc<Surface<Window>>,
-    device: Arc<Device>,
-    graphics_queue: Arc<Queue>,
-    present_queue: Arc<Queue>,
-) -> Result<(Arc<Swapchain<Window>>, Vec<Arc<SwapchainImage<Window>>>)> {
-    let capabilities = surface.capabilities(device.physical_device())?;
+pub fn build_sharing_mode(families: &[QueueFamily]) -> SharingMode {
+    sharing_mode_from_ids(families.iter().map(|family| family.id()))
+}
 
-    let usage = ImageUsage {
-        color_attachment: true,
-        ..ImageUsage::none()
-    };
+// Vulkan requires the concurrent family indices to be unique
+fn sharing_mode_from_ids(ids: impl IntoIterator<Item = u32>) -> SharingMode {
+    let mut ids: Vec<_> = ids.into_iter().collect();
+    ids.sort_unstable();
+    ids.dedup();
 
-    let sharing_mode = if graphics_queue.family() != present_queue.family() {
-        SharingMode::Concurrent(vec![
-            graphics_queue.family().id(),
-            present_queue.family().id(),
-        ])
+    if ids.len() > 1 {
+        SharingMode::Concurrent(ids)
     } else {
         SharingMode::Exclusive
-    };
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn create_swapchain<W>(
+    surface: Arc<Surface<W>>,
+    device: Arc<Device>,
+    graphics_queue: Arc<Queue>,
+    present_queue: Arc<Queue>,
+    dimensions: [u32; 2],
+    usage: ImageUsage,
+    latency: Option<Latency>,
+    present_mode: Option<PresentModePreference>,
+    frames_in_flight: Option<usize>,
+    old_swapchain: Option<Arc<Swapchain<W>>>,
+) -> Result<(
+    Arc<Swapchain<W>>,
+    Vec<Arc<SwapchainImage<W>>>,
+    PresentationProfile,
+)> {
+    //
+    let capabilities = surface.capabilities(device.physical_device())?;
 
-    let num_images =
-        (capabilities.min_image_count + 1).min(capabilities.max_image_count.unwrap_or(u32::MAX));
+    let sharing_mode = build_sharing_mode(&[graphics_queue.family(), present_queue.family()]);
 
     let (format, color_space) = capabilities
         .supported_formats
@@ -193,75 +394,216 @@ pub fn create_swapchain(
         .cloned()
         .unwrap_or(capabilities.supported_formats[0]);
 
-    let present_mode = if capabilities.present_modes.mailbox {
-        PresentMode::Mailbox
-    } else if capabilities.present_modes.immediate {
-        PresentMode::Immediate
-    } else {
-        PresentMode::Fifo
+    let limits = PresentationLimits::from(&capabilities);
+    let profile = PresentationProfile::resolve(latency, present_mode, frames_in_flight, &limits);
+    info!(
+        "Presentation profile: {} swapchain images, {:?} present mode, {} frames in flight",
+        profile.image_count, profile.present_mode, profile.frames_in_flight
+    );
+
+    // Passing the old swapchain lets the driver reuse its resources and hand over presentation
+    let (swapchain, images) = match old_swapchain {
+        Some(old_swapchain) => Swapchain::with_old_swapchain(
+            device,
+            surface.clone(),
+            profile.image_count,
+            format,
+            dimensions,
+            1,
+            usage,
+            sharing_mode,
+            SurfaceTransform::Identity,
+            CompositeAlpha::Opaque,
+            profile.present_mode,
+            FullscreenExclusive::Default,
+            true,
+            color_space,
+            old_swapchain,
+        )?,
+        None => Swapchain::new(
+            device,
+            surface.clone(),
+            profile.image_count,
+            format,
+            dimensions,
+            1,
+            usage,
+            sharing_mode,
+            SurfaceTransform::Identity,
+            CompositeAlpha::Opaque,
+            profile.present_mode,
+            FullscreenExclusive::Default,
+            true,
+            color_space,
+        )?,
     };
 
-    Ok(Swapchain::new(
-        device,
-        surface.clone(),
-        num_images,
-        format,
-        surface.window().inner_size().into(),
-        1,
-        usage,
-        sharing_mode,
-        SurfaceTransform::Identity,
-        CompositeAlpha::Opaque,
-        present_mode,
-        FullscreenExclusive::Default,
-        true,
-        color_space,
-    )?)
-}
-
-pub fn create_buffers(graphics_queue: Arc<Queue>) -> Result<(VertexBuffer, IndexBuffer)> {
-    let (models, _) = tobj::load_obj("assets/lfs/models/chalet.obj", true)?;
-    let mesh = &models[0].mesh;
-
-    let (vertex_buffer, vertex_future) = ImmutableBuffer::from_iter(
-        mesh.positions
-            .chunks_exact(3)
-            .zip(mesh.texcoords.chunks_exact(2))
-            .map(|(pos, tex)| Vertex {
-                position: [pos[0], pos[1], pos[2]],
-                texture_coords: [tex[0], 1.0 - tex[1]],
-            }),
-        BufferUsage::vertex_buffer(),
-        graphics_queue.clone(),
-    )?;
+    Ok((swapchain, images, profile))
+}
 
-    let (index_buffer, index_future) = ImmutableBuffer::from_iter(
-        mesh.indices.iter().cloned(),
-        BufferUsage::index_buffer(),
-        graphics_queue,
-    )?;
+pub fn create_buffers(
+    graphics_queue: Arc<Queue>,
+    asset_root: &AssetRoot,
+    model_path: Option<&str>,
+    up_axis: Option<UpAxis>,
+    unit_scale: Option<f32>,
+) -> Result<(Vec<MeshBuffers>, glm::Mat4)> {
+    //
+    // An explicitly requested model must load, only the default one falls back to the cube
+    let models = match model_path {
+        Some(path) => load_model(asset_root, path)?,
+        None => {
+            let path = "assets/lfs/models/chalet.obj";
+            load_model(asset_root, path).or_else(|e| {
+                warn!("Failed to load model {path}, using the builtin cube instead: {e:?}");
+                load_model(asset_root, "builtin:cube")
+            })?
+        }
+    };
+    let meshes: Vec<_> = models
+        .iter()
+        .map(|model| &model.mesh)
+        .filter(|mesh| !mesh.indices.is_empty())
+        .collect();
 
-    vertex_future
-        .join(index_future)
-        .then_signal_fence_and_flush()?
-        .cleanup_finished();
+    // The correction is guessed from the whole model, so every mesh gets the same transform
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    for mesh in &meshes {
+        let offset = (positions.len() / 3) as u32;
+        positions.extend_from_slice(&mesh.positions);
+        indices.extend(mesh.indices.iter().map(|index| index + offset));
+    }
+
+    let root_transform = match Aabb::from_positions(&positions) {
+        Some(aabb) => {
+            let up_axis = up_axis.unwrap_or_else(|| guess_up_axis(&positions, &indices, &aabb));
+            let unit_scale = unit_scale.unwrap_or_else(|| guess_unit_scale(&aabb));
+            info!("Model correction: up axis {up_axis:?}, unit scale {unit_scale}");
+            correction_transform(up_axis, unit_scale)
+        }
+        None => glm::identity(),
+    };
+
+    let mut buffers = Vec::with_capacity(meshes.len());
+    let mut unique_vertex_count = 0;
+
+    for mesh in meshes {
+        let (vertices, indices) = deduplicate_vertices(
+            &mesh.positions,
+            &mesh.normals,
+            &mesh.texcoords,
+            &mesh.indices,
+        );
+        unique_vertex_count += vertices.len();
+        let index_type = index_type(vertices.len());
+
+        let (vertex_buffer, vertex_future) = ImmutableBuffer::from_iter(
+            vertices.into_iter(),
+            BufferUsage::vertex_buffer(),
+            graphics_queue.clone(),
+        )?;
+
+        let (index_buffer, index_future) = match index_type {
+            IndexType::U16 => {
+                let (buffer, future) = ImmutableBuffer::from_iter(
+                    indices.into_iter().map(|index| index as u16),
+                    BufferUsage::index_buffer(),
+                    graphics_queue.clone(),
+                )?;
+                (IndexBuffer::U16(buffer), future)
+            }
+            IndexType::U32 => {
+                let (buffer, future) = ImmutableBuffer::from_iter(
+                    indices.into_iter(),
+                    BufferUsage::index_buffer(),
+                    graphics_queue.clone(),
+                )?;
+                (IndexBuffer::U32(buffer), future)
+            }
+        };
+
+        vertex_future
+            .join(index_future)
+            .then_signal_fence_and_flush()?
+            .cleanup_finished();
+
+        buffers.push((vertex_buffer, index_buffer));
+    }
+
+    info!(
+        "Loaded {} meshes, deduplicated vertices: {} -> {unique_vertex_count}",
+        buffers.len(),
+        positions.len() / 3,
+    );
+
+    Ok((buffers, root_transform))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndexType {
+    U16,
+    U32,
+}
+
+// Deduplicated indices only refer to the mesh's own vertices, so the vertex count bounds them
+fn index_type(vertex_count: usize) -> IndexType {
+    if vertex_count <= u16::MAX as usize + 1 {
+        IndexType::U16
+    } else {
+        IndexType::U32
+    }
+}
 
-    Ok((vertex_buffer, index_buffer))
+fn load_model(asset_root: &AssetRoot, path: &str) -> Result<Vec<tobj::Model>> {
+    match builtin(path) {
+        Some(bytes) => load_model_from_bytes(bytes),
+        None => load_model_from_bytes(&asset_root.read(path)?),
+    }
+}
+
+fn load_model_from_bytes(mut bytes: &[u8]) -> Result<Vec<tobj::Model>> {
+    let (models, _) = tobj::load_obj_buf(&mut bytes, true, |_| Ok(Default::default()))?;
+    Ok(models)
+}
+
+pub fn load_texture(
+    graphics_queue: Arc<Queue>,
+    asset_root: &AssetRoot,
+    texture_path: Option<&str>,
+) -> Result<Arc<ImmutableImage<Format>>> {
+    //
+    let path = texture_path.unwrap_or("assets/lfs/textures/chalet.jpg");
+    upload_texture(
+        graphics_queue,
+        decode_texture_or_placeholder(asset_root, path),
+    )
+}
+
+pub fn create_uv_test_texture(graphics_queue: Arc<Queue>) -> Result<Arc<ImmutableImage<Format>>> {
+    upload_texture(graphics_queue, uv_test_texture())
 }
 
-pub fn load_texture(graphics_queue: Arc<Queue>) -> Result<Arc<ImmutableImage<Format>>> {
-    let img = image::open("assets/lfs/textures/chalet.jpg")?;
+fn upload_texture(
+    graphics_queue: Arc<Queue>,
+    img: DynamicImage,
+) -> Result<Arc<ImmutableImage<Format>>> {
+    //
     let (width, height) = img.dimensions();
 
+    let format = pick_texture_format(graphics_queue.device().physical_device());
+    let pixels = texture_pixels(&img, format);
+
+    // vulkano fills each level below the base one by blitting the previous level into it
     let (texture, texture_future) = ImmutableImage::from_iter(
-        img.to_bytes().into_iter(),
+        pixels.into_iter(),
         ImageDimensions::Dim2d {
             width,
             height,
             array_layers: 1,
         },
-        MipmapsCount::One,
-        Format::R8G8B8Srgb,
+        MipmapsCount::Specific(mip_levels(width, height)),
+        format,
         graphics_queue,
     )?;
 
@@ -272,7 +614,156 @@ pub fn load_texture(graphics_queue: Arc<Queue>) -> Result<Arc<ImmutableImage<For
     Ok(texture)
 }
 
+// Three-channel formats are optional, unlike R8G8B8A8Srgb which is always sampleable and blittable
+fn pick_texture_format(physical_device: PhysicalDevice) -> Format {
+    let features = Format::R8G8B8Srgb
+        .properties(physical_device)
+        .optimal_tiling_features;
+
+    let rgb_usable = features.sampled_image
+        && features.sampled_image_filter_linear
+        && features.blit_src
+        && features.blit_dst;
+
+    if !rgb_usable {
+        warn!("R8G8B8Srgb textures are not supported by the device, using R8G8B8A8Srgb instead");
+    }
+    texture_format(rgb_usable)
+}
+
+fn texture_format(rgb_usable: bool) -> Format {
+    if rgb_usable {
+        Format::R8G8B8Srgb
+    } else {
+        Format::R8G8B8A8Srgb
+    }
+}
+
+// Pixels staged for upload, with as many channels as the texture format
+fn texture_pixels(img: &DynamicImage, format: Format) -> Vec<u8> {
+    match format {
+        Format::R8G8B8Srgb => img.to_rgb8().into_raw(),
+        _ => img.to_rgba8().into_raw(),
+    }
+}
+
+// Each level is half the previous one (rounded down, at least 1), down to 1x1
+fn mip_levels(width: u32, height: u32) -> u32 {
+    32 - width.max(height).leading_zeros()
+}
+
+// A missing, unreadable or unsupported texture never stops the app from starting
+fn decode_texture_or_placeholder(asset_root: &AssetRoot, path: &str) -> DynamicImage {
+    decode_texture(asset_root, path).unwrap_or_else(|e| {
+        warn!("Failed to load texture {path}, using the error texture instead: {e:?}");
+        error_texture()
+    })
+}
+
+fn decode_texture(asset_root: &AssetRoot, path: &str) -> Result<DynamicImage> {
+    match path {
+        "builtin:white" => Ok(white_texture()),
+        "builtin:checker" => Ok(error_texture()),
+        "builtin:uv-test" => Ok(uv_test_texture()),
+        _ => Ok(image::load_from_memory(&asset_root.read(path)?)?),
+    }
+}
+
+fn white_texture() -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_pixel(1, 1, Rgb([255, 255, 255])))
+}
+
+fn error_texture() -> DynamicImage {
+    const SIZE: u32 = 64;
+    const CELL_SIZE: u32 = 8;
+
+    DynamicImage::ImageRgb8(RgbImage::from_fn(SIZE, SIZE, |x, y| {
+        if (x / CELL_SIZE + y / CELL_SIZE).is_multiple_of(2) {
+            Rgb([255, 0, 255])
+        } else {
+            Rgb([0, 0, 0])
+        }
+    }))
+}
+
+// 3x5 digit glyphs, one bit per pixel, row by row from the most significant bit
+const DIGIT_GLYPHS: [u16; 10] = [
+    0b111_101_101_101_111,
+    0b010_110_010_010_111,
+    0b111_001_111_100_111,
+    0b111_001_111_001_111,
+    0b101_101_111_001_001,
+    0b111_100_111_001_111,
+    0b111_100_111_101_111,
+    0b111_001_001_001_001,
+    0b111_101_111_101_111,
+    0b111_101_111_001_111,
+];
+
+// Numbered cells in red, green, blue and yellow quadrants (from the top left), framed by a white
+// one-pixel border which shows whether the texture edges are reached or cropped
+fn uv_test_texture() -> DynamicImage {
+    const SIZE: u32 = 512;
+    const CELL_SIZE: u32 = 64;
+    const QUADRANT_COLORS: [[u8; 3]; 4] =
+        [[255, 64, 64], [64, 255, 64], [64, 64, 255], [255, 255, 64]];
+
+    DynamicImage::ImageRgb8(RgbImage::from_fn(SIZE, SIZE, |x, y| {
+        if x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1 {
+            return Rgb([255, 255, 255]);
+        }
+
+        let (column, row) = (x / CELL_SIZE, y / CELL_SIZE);
+        let (cell_x, cell_y) = (x % CELL_SIZE, y % CELL_SIZE);
+        let number = row * (SIZE / CELL_SIZE) + column;
+
+        if cell_x == 0 || cell_y == 0 || is_label_pixel(number, cell_x, cell_y) {
+            return Rgb([0, 0, 0]);
+        }
+
+        let color = QUADRANT_COLORS[(2 * y / SIZE * 2 + 2 * x / SIZE) as usize];
+        if (column + row) % 2 == 0 {
+            Rgb(color)
+        } else {
+            Rgb(color.map(|c| c / 2))
+        }
+    }))
+}
+
+// Two-digit label in the top left corner of a cell
+fn is_label_pixel(number: u32, x: u32, y: u32) -> bool {
+    const ORIGIN: u32 = 8;
+    const SCALE: u32 = 4;
+
+    if x < ORIGIN || y < ORIGIN {
+        return false;
+    }
+
+    // Glyphs are 3 pixels wide with 1 pixel of spacing
+    let (glyph_x, glyph_y) = ((x - ORIGIN) / SCALE, (y - ORIGIN) / SCALE);
+    let (digit_index, column) = (glyph_x / 4, glyph_x % 4);
+    if digit_index >= 2 || column == 3 || glyph_y >= 5 {
+        return false;
+    }
+
+    let digit = if digit_index == 0 {
+        number / 10 % 10
+    } else {
+        number % 10
+    };
+    DIGIT_GLYPHS[digit as usize] >> (14 - (3 * glyph_y + column)) & 1 == 1
+}
+
 pub fn create_sampler(device: Arc<Device>) -> Result<Arc<Sampler>> {
+    let max_anisotropy = if device.enabled_features().sampler_anisotropy {
+        let max_anisotropy = device.physical_device().limits().max_sampler_anisotropy();
+        info!("Anisotropic filtering: {max_anisotropy}x");
+        max_anisotropy
+    } else {
+        info!("Anisotropic filtering: not supported by the device");
+        1.0
+    };
+
     let sampler = Sampler::new(
         device.clone(),
         Filter::Linear,
@@ -282,18 +773,83 @@ pub fn create_sampler(device: Arc<Device>) -> Result<Arc<Sampler>> {
         SamplerAddressMode::Repeat,
         SamplerAddressMode::Repeat,
         0.0,
-        device.physical_device().limits().max_sampler_anisotropy(),
+        max_anisotropy,
         0.0,
         1000.0,
     )?;
     Ok(sampler)
 }
 
-pub fn create_render_pass(
+pub fn find_depth_format(physical_device: PhysicalDevice) -> Result<Format> {
+    const CANDIDATES: [Format; 4] = [
+        Format::D32Sfloat,
+        Format::D32Sfloat_S8Uint,
+        Format::D24Unorm_S8Uint,
+        Format::D16Unorm,
+    ];
+
+    CANDIDATES
+        .iter()
+        .copied()
+        .find(|format| {
+            format
+                .properties(physical_device)
+                .optimal_tiling_features
+                .depth_stencil_attachment
+        })
+        .ok_or(Error::NoSuitableDepthFormat)
+}
+
+pub fn pick_sample_count(physical_device: PhysicalDevice, requested: u32) -> u32 {
+    let limits = physical_device.limits();
+    let supported =
+        limits.framebuffer_color_sample_counts() & limits.framebuffer_depth_sample_counts();
+
+    // Sample count flags have the same value as the count they represent
+    let mut samples = 1 << (31 - requested.max(1).leading_zeros());
+    while samples > 1 && supported & samples == 0 {
+        samples /= 2;
+    }
+    samples
+}
+
+pub fn create_render_pass<W>(
     device: Arc<Device>,
-    swapchain: Arc<Swapchain<Window>>,
+    swapchain: Arc<Swapchain<W>>,
+    depth_format: Format,
+    samples: u32,
 ) -> Result<Arc<dyn RenderPassAbstract + Send + Sync>> {
     //
+    if samples > 1 {
+        return Ok(Arc::new(vulkano::single_pass_renderpass!(device,
+            attachments: {
+                intermediary: {
+                    load: Clear,
+                    store: DontCare,
+                    format: swapchain.format(),
+                    samples: samples,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: depth_format,
+                    samples: samples,
+                },
+                color: {
+                    load: DontCare,
+                    store: Store,
+                    format: swapchain.format(),
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [intermediary],
+                depth_stencil: {depth},
+                resolve: [color]
+            }
+        )?));
+    }
+
     Ok(Arc::new(vulkano::single_pass_renderpass!(device,
         attachments: {
             color: {
@@ -305,7 +861,7 @@ pub fn create_render_pass(
             depth: {
                 load: Clear,
                 store: DontCare,
-                format: Format::D32Sfloat,
+                format: depth_format,
                 samples: 1,
             }
         },
@@ -318,68 +874,387 @@ pub fn create_render_pass(
 
 pub fn create_pipeline(
     render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    shaders: Option<&Shaders>,
+    options: PipelineOptions,
 ) -> Result<Arc<dyn GraphicsPipelineAbstract + Send + Sync>> {
     //
-    let device = render_pass.device();
-    Ok(Arc::new(
-        GraphicsPipeline::start()
-            .vertex_input_single_buffer::<Vertex>()
-            .vertex_shader(vs::Shader::load(device.clone())?.main_entry_point(), ())
-            .triangle_list()
-            .viewports_dynamic_scissors_irrelevant(1)
-            .fragment_shader(fs::Shader::load(device.clone())?.main_entry_point(), ())
-            .depth_stencil_simple_depth()
-            .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
-            .build(device.clone())?,
-    ))
-}
-
-pub fn update_dynamic_viewport(
-    swapchain: Arc<Swapchain<Window>>,
-    dynamic_state: &mut DynamicState,
-) {
-    //
-    const RATIO: f32 = WIDTH as f32 / HEIGHT as f32;
+    match shaders {
+        Some(shaders) => build_pipeline(
+            render_pass,
+            shaders.vertex_entry_point(),
+            shaders.fragment_entry_point(),
+            options,
+        ),
+        None => {
+            let device = render_pass.device().clone();
+            let vertex_shader = vs::Shader::load(device.clone())?;
+            let fragment_shader = fs::Shader::load(device)?;
+            build_pipeline(
+                render_pass,
+                vertex_shader.main_entry_point(),
+                fragment_shader.main_entry_point(),
+                options,
+            )
+        }
+    }
+}
 
-    let dimensions = swapchain.dimensions();
-    let (mut width, mut height) = (dimensions[0] as f32, dimensions[1] as f32);
+// The wireframe pipeline is prebuilt so toggling it doesn't rebuild anything mid-frame
+#[allow(clippy::type_complexity)]
+pub fn create_pipelines(
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    shaders: Option<&Shaders>,
+    options: PipelineOptions,
+) -> Result<(
+    Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    Option<Arc<dyn GraphicsPipelineAbstract + Send + Sync>>,
+)> {
+    //
+    let solid_pipeline = create_pipeline(render_pass.clone(), shaders, options)?;
 
-    if width / height > RATIO {
-        width = RATIO * height;
+    let wireframe_pipeline = if render_pass.device().enabled_features().fill_mode_non_solid {
+        let options = PipelineOptions {
+            polygon_mode: PolygonMode::Line,
+            ..options
+        };
+        Some(create_pipeline(render_pass, shaders, options)?)
     } else {
-        height = width / RATIO;
-    }
+        None
+    };
+
+    Ok((solid_pipeline, wireframe_pipeline))
+}
+
+fn build_pipeline(
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    vertex_entry_point: GraphicsEntryPoint<(), vs::MainInput, vs::MainOutput, vs::MainLayout>,
+    fragment_entry_point: GraphicsEntryPoint<(), fs::MainInput, fs::MainOutput, fs::MainLayout>,
+    options: PipelineOptions,
+) -> Result<Arc<dyn GraphicsPipelineAbstract + Send + Sync>> {
+    //
+    let device = render_pass.device();
+
+    let builder = GraphicsPipeline::start()
+        .vertex_input_single_buffer::<Vertex>()
+        .vertex_shader(vertex_entry_point, ())
+        .triangle_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .fragment_shader(fragment_entry_point, ())
+        .depth_stencil_simple_depth()
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap());
+
+    let builder = match options.cull_mode {
+        CullMode::None => builder.cull_mode_disabled(),
+        CullMode::Front => builder.cull_mode_front(),
+        CullMode::Back => builder.cull_mode_back(),
+        CullMode::FrontAndBack => builder.cull_mode_front_and_back(),
+    };
+
+    let builder = match options.front_face {
+        FrontFace::CounterClockwise => builder.front_face_counter_clockwise(),
+        FrontFace::Clockwise => builder.front_face_clockwise(),
+    };
 
-    let dimensions = swapchain.dimensions();
+    let builder = match options.polygon_mode {
+        PolygonMode::Fill => builder.polygon_mode_fill(),
+        PolygonMode::Line => builder.polygon_mode_line(),
+        PolygonMode::Point => builder.polygon_mode_point(),
+    };
+
+    Ok(Arc::new(builder.build(device.clone())?))
+}
+
+pub fn update_dynamic_viewport<W>(
+    swapchain: Arc<Swapchain<W>>,
+    letterbox: Letterbox,
+    dynamic_state: &mut DynamicState,
+) {
+    //
+    let (origin, dimensions) = letterbox.viewport(swapchain.dimensions());
     dynamic_state.viewports = Some(vec![Viewport {
-        origin: [
-            (dimensions[0] as f32 - width) / 2.0,
-            (dimensions[1] as f32 - height) / 2.0,
-        ],
-        dimensions: [width, height],
+        origin,
+        dimensions,
         depth_range: 0.0..1.0,
     }]);
 }
 
-pub fn create_framebuffers(
-    swapchain_images: Vec<Arc<SwapchainImage<Window>>>,
+pub fn create_framebuffers<W: Send + Sync + 'static>(
+    swapchain_images: Vec<Arc<SwapchainImage<W>>>,
     render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    depth_format: Format,
+    samples: u32,
 ) -> Result<Vec<Arc<dyn FramebufferAbstract + Send + Sync>>> {
     //
-    let depth_buffer = AttachmentImage::transient(
-        render_pass.device().clone(),
-        swapchain_images[0].dimensions(),
-        Format::D32Sfloat,
-    )?;
+    let device = render_pass.device().clone();
 
     let mut framebuffers = Vec::<Arc<dyn FramebufferAbstract + Send + Sync>>::new();
+
+    // Every framebuffer gets its own attachments, frames in flight would otherwise write to the
+    // same depth (and multisampled color) image concurrently. The framebuffers keep them alive.
     for image in swapchain_images {
-        framebuffers.push(Arc::new(
-            Framebuffer::start(render_pass.clone())
-                .add(ImageView::new(image.clone())?)?
-                .add(ImageView::new(depth_buffer.clone())?)?
-                .build()?,
-        ));
+        let dimensions = image.dimensions();
+
+        if samples > 1 {
+            let color_buffer = AttachmentImage::transient_multisampled(
+                device.clone(),
+                dimensions,
+                samples,
+                image.swapchain().format(),
+            )?;
+            let depth_buffer = AttachmentImage::transient_multisampled(
+                device.clone(),
+                dimensions,
+                samples,
+                depth_format,
+            )?;
+
+            framebuffers.push(Arc::new(
+                Framebuffer::start(render_pass.clone())
+                    .add(ImageView::new(color_buffer)?)?
+                    .add(ImageView::new(depth_buffer)?)?
+                    .add(ImageView::new(image)?)?
+                    .build()?,
+            ));
+        } else {
+            let depth_buffer =
+                AttachmentImage::transient(device.clone(), dimensions, depth_format)?;
+
+            framebuffers.push(Arc::new(
+                Framebuffer::start(render_pass.clone())
+                    .add(ImageView::new(image)?)?
+                    .add(ImageView::new(depth_buffer)?)?
+                    .build()?,
+            ));
+        }
     }
     Ok(framebuffers)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concurrent_ids(sharing_mode: SharingMode) -> Vec<u32> {
+        match sharing_mode {
+            SharingMode::Concurrent(ids) => ids.as_slice().to_vec(),
+            SharingMode::Exclusive => panic!("expected a concurrent sharing mode"),
+        }
+    }
+
+    fn family(id: u32, graphics: bool, present: bool) -> FamilyProperties {
+        FamilyProperties {
+            id,
+            graphics,
+            present,
+        }
+    }
+
+    #[test]
+    fn combined_family_is_preferred() {
+        let families = [
+            family(0, true, false),
+            family(1, false, true),
+            family(2, true, true),
+        ];
+        assert_eq!(select_families(&families), Some((2, 2)));
+    }
+
+    #[test]
+    fn separate_families_are_used_without_a_combined_one() {
+        let families = [family(0, true, false), family(1, false, true)];
+        assert_eq!(select_families(&families), Some((0, 1)));
+
+        assert_eq!(select_families(&[family(0, true, false)]), None);
+    }
+
+    #[test]
+    fn single_combined_family_is_exclusive() {
+        let (graphics, present) = select_families(&[family(0, true, true)]).unwrap();
+
+        assert!(matches!(
+            sharing_mode_from_ids([graphics, present]),
+            SharingMode::Exclusive
+        ));
+    }
+
+    #[test]
+    fn duplicate_families_are_removed() {
+        assert_eq!(concurrent_ids(sharing_mode_from_ids([1, 0, 1])), [0, 1]);
+    }
+
+    #[test]
+    fn separate_graphics_present_and_transfer_families_are_concurrent() {
+        assert_eq!(concurrent_ids(sharing_mode_from_ids([2, 0, 1])), [0, 1, 2]);
+    }
+
+    #[test]
+    fn small_meshes_use_16_bit_indices() {
+        assert_eq!(index_type(3), IndexType::U16);
+        assert_eq!(index_type(65_536), IndexType::U16);
+        assert_eq!(index_type(65_537), IndexType::U32);
+        assert_eq!(index_type(70_000), IndexType::U32);
+    }
+
+    #[test]
+    fn rgba_is_the_fallback_texture_format() {
+        assert_eq!(texture_format(true), Format::R8G8B8Srgb);
+        assert_eq!(texture_format(false), Format::R8G8B8A8Srgb);
+    }
+
+    #[test]
+    fn rgb_pixels_are_expanded_for_rgba_textures() {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(3, 2, Rgb([10, 20, 30])));
+
+        let pixels = texture_pixels(&img, Format::R8G8B8A8Srgb);
+        assert_eq!(pixels.len(), 3 * 2 * 4);
+        assert_eq!(pixels[..4], [10, 20, 30, 255]);
+
+        assert_eq!(texture_pixels(&img, Format::R8G8B8Srgb).len(), 3 * 2 * 3);
+    }
+
+    #[test]
+    fn white_texture_is_a_single_white_pixel() {
+        let texture = white_texture();
+
+        assert_eq!(texture.dimensions(), (1, 1));
+        assert_eq!(texture.to_rgb8().get_pixel(0, 0), &Rgb([255, 255, 255]));
+    }
+
+    #[test]
+    fn error_texture_is_a_magenta_and_black_checkerboard() {
+        let texture = error_texture().to_rgb8();
+        let (magenta, black) = (Rgb([255, 0, 255]), Rgb([0, 0, 0]));
+
+        assert_eq!(texture.dimensions(), (64, 64));
+        assert_eq!(texture.get_pixel(0, 0), &magenta);
+        assert_eq!(texture.get_pixel(7, 7), &magenta);
+        assert_eq!(texture.get_pixel(8, 0), &black);
+        assert_eq!(texture.get_pixel(0, 8), &black);
+        assert_eq!(texture.get_pixel(8, 8), &magenta);
+        assert_eq!(texture.get_pixel(63, 63), &magenta);
+    }
+
+    #[test]
+    fn textures_that_fail_to_load_are_replaced_by_the_error_texture() {
+        let root = tempfile::tempdir().unwrap();
+        let textures = root.path().join("textures");
+        std::fs::create_dir_all(textures.join("unreadable.png")).unwrap();
+        std::fs::write(textures.join("unsupported.png"), "not an image").unwrap();
+        std::fs::write(textures.join("white.ppm"), "P3 1 1 255 255 255 255").unwrap();
+
+        let asset_root = AssetRoot::new(Some(root.path().to_owned()), None).unwrap();
+        let error_pixels = error_texture().to_rgb8().into_raw();
+
+        for path in [
+            "textures/missing.png",
+            "textures/unreadable.png",
+            "textures/unsupported.png",
+        ] {
+            let texture = decode_texture_or_placeholder(&asset_root, path);
+            assert_eq!(texture.to_rgb8().into_raw(), error_pixels, "{path}");
+        }
+
+        let texture = decode_texture_or_placeholder(&asset_root, "textures/white.ppm");
+        assert_eq!(texture.to_rgb8().into_raw(), [255, 255, 255]);
+    }
+
+    #[test]
+    fn uv_test_texture_has_a_white_border_and_colored_quadrants() {
+        let img = uv_test_texture().to_rgb8();
+        let last = img.width() - 1;
+
+        for (x, y) in [(0, 0), (last, 0), (0, last), (last, last)] {
+            assert_eq!(img.get_pixel(x, y), &Rgb([255, 255, 255]));
+        }
+
+        // Just inside the border and the grid lines, in the first cell of each quadrant
+        let half = img.width() / 2;
+        assert_eq!(img.get_pixel(1, 1), &Rgb([255, 64, 64]));
+        assert_eq!(img.get_pixel(half + 1, 1), &Rgb([64, 255, 64]));
+        assert_eq!(img.get_pixel(1, half + 1), &Rgb([64, 64, 255]));
+        assert_eq!(img.get_pixel(half + 1, half + 1), &Rgb([255, 255, 64]));
+    }
+
+    #[test]
+    fn uv_test_texture_repeats_every_two_cells() {
+        const CELL_SIZE: u32 = 64;
+        let img = uv_test_texture().to_rgb8();
+
+        // Grid lines start every cell
+        for column in 1..8 {
+            assert_eq!(img.get_pixel(column * CELL_SIZE, 100), &Rgb([0, 0, 0]));
+            assert_ne!(img.get_pixel(column * CELL_SIZE + 1, 100), &Rgb([0, 0, 0]));
+        }
+
+        // Away from the labels, cells alternate between full and half brightness
+        let (x, y) = (48, 48);
+        assert_eq!(img.get_pixel(x, y), &Rgb([255, 64, 64]));
+        assert_eq!(img.get_pixel(x + CELL_SIZE, y), &Rgb([127, 32, 32]));
+        assert_eq!(img.get_pixel(x, y + CELL_SIZE), &Rgb([127, 32, 32]));
+        assert_eq!(img.get_pixel(x + 2 * CELL_SIZE, y), img.get_pixel(x, y));
+        assert_eq!(
+            img.get_pixel(x + CELL_SIZE, y + CELL_SIZE),
+            img.get_pixel(x, y)
+        );
+    }
+
+    #[test]
+    fn verbose_messages_are_only_requested_when_logged() {
+        let info = message_severity(LevelFilter::Info);
+        assert!(info.error && info.warning && !info.information && !info.verbose);
+
+        let debug = message_severity(LevelFilter::Debug);
+        assert!(debug.error && debug.warning && debug.information && !debug.verbose);
+
+        let trace = message_severity(LevelFilter::Trace);
+        assert!(trace.error && trace.warning && trace.information && trace.verbose);
+    }
+
+    #[test]
+    fn only_errors_are_counted() {
+        let before = validation_error_count();
+
+        let error = MessageSeverity {
+            error: true,
+            ..MessageSeverity::none()
+        };
+        log_message(error, "validation", "VUID-test", "error");
+        log_message(error, "validation", "VUID-test", "error");
+        log_message(
+            MessageSeverity::errors_and_warnings(),
+            "general",
+            "test",
+            "error",
+        );
+        log_message(
+            MessageSeverity {
+                error: true,
+                warning: true,
+                information: true,
+                verbose: true,
+            },
+            "general",
+            "test",
+            "error",
+        );
+
+        for severity in [
+            MessageSeverity {
+                warning: true,
+                ..MessageSeverity::none()
+            },
+            MessageSeverity {
+                information: true,
+                ..MessageSeverity::none()
+            },
+            MessageSeverity {
+                verbose: true,
+                ..MessageSeverity::none()
+            },
+        ] {
+            log_message(severity, "performance", "test", "not an error");
+        }
+
+        assert_eq!(validation_error_count() - before, 4);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Letterbox {
+    Window,
+    Standard,
+    Widescreen,
+    Cinema,
+    Square,
+}
+
+impl Letterbox {
+    pub fn ratio(self) -> Option<f32> {
+        match self {
+            Letterbox::Window => None,
+            Letterbox::Standard => Some(4.0 / 3.0),
+            Letterbox::Widescreen => Some(16.0 / 9.0),
+            Letterbox::Cinema => Some(21.0 / 9.0),
+            Letterbox::Square => Some(1.0),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Letterbox::Window => Letterbox::Standard,
+            Letterbox::Standard => Letterbox::Widescreen,
+            Letterbox::Widescreen => Letterbox::Cinema,
+            Letterbox::Cinema => Letterbox::Square,
+            Letterbox::Square => Letterbox::Window,
+        }
+    }
+
+    // Size and origin of the largest viewport with the target ratio, centered in the window
+    pub fn viewport(self, window_dimensions: [u32; 2]) -> ([f32; 2], [f32; 2]) {
+        let (window_width, window_height) =
+            (window_dimensions[0] as f32, window_dimensions[1] as f32);
+
+        let (width, height) = match self.ratio() {
+            Some(ratio) if window_width / window_height > ratio => {
+                (ratio * window_height, window_height)
+            }
+            Some(ratio) => (window_width, window_width / ratio),
+            None => (window_width, window_height),
+        };
+
+        let origin = [(window_width - width) / 2.0, (window_height - height) / 2.0];
+        (origin, [width, height])
+    }
+}
+
+impl fmt::Display for Letterbox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Letterbox::Window => "window",
+            Letterbox::Standard => "4:3",
+            Letterbox::Widescreen => "16:9",
+            Letterbox::Cinema => "21:9",
+            Letterbox::Square => "1:1",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Letterbox {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "window" => Ok(Letterbox::Window),
+            "4:3" => Ok(Letterbox::Standard),
+            "16:9" => Ok(Letterbox::Widescreen),
+            "21:9" => Ok(Letterbox::Cinema),
+            "1:1" => Ok(Letterbox::Square),
+            _ => Err(format!("expected window, 4:3, 16:9, 21:9 or 1:1, got {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_content_is_pillarboxed_in_a_widescreen_window() {
+        let (origin, dimensions) = Letterbox::Standard.viewport([1920, 1080]);
+        assert_eq!(origin, [240.0, 0.0]);
+        assert_eq!(dimensions, [1440.0, 1080.0]);
+    }
+
+    #[test]
+    fn cinema_content_is_letterboxed_in_a_standard_window() {
+        let (origin, dimensions) = Letterbox::Cinema.viewport([840, 630]);
+        assert_eq!(origin, [0.0, 135.0]);
+        assert_eq!(dimensions, [840.0, 360.0]);
+    }
+
+    #[test]
+    fn window_preset_covers_the_whole_window() {
+        for window_dimensions in [[800, 600], [1920, 1080], [640, 1280]] {
+            let (origin, dimensions) = Letterbox::Window.viewport(window_dimensions);
+            assert_eq!(origin, [0.0, 0.0]);
+            assert_eq!(
+                dimensions,
+                [window_dimensions[0] as f32, window_dimensions[1] as f32]
+            );
+        }
+    }
+
+    #[test]
+    fn matching_ratio_covers_the_whole_window() {
+        let (origin, dimensions) = Letterbox::Widescreen.viewport([1280, 720]);
+        assert_eq!(origin, [0.0, 0.0]);
+        assert_eq!(dimensions, [1280.0, 720.0]);
+    }
+}
@@ -1,18 +1,46 @@
+pub mod assets;
+pub mod audio;
+pub mod camera;
+pub mod clock;
+pub mod color;
+pub mod error;
+pub mod focus;
+pub mod init;
+pub mod letterbox;
+pub mod mesh;
+pub mod pipeline;
+pub mod presentation;
+pub mod renderer;
+pub mod screenshot;
+pub mod shaders;
+pub mod stats;
+pub mod turntable;
+
 use std::sync::Arc;
 use vulkano::buffer::ImmutableBuffer;
 
 pub const WIDTH: u32 = 800;
 pub const HEIGHT: u32 = 600;
+pub const TITLE: &str = "Vulkan Application";
 
 #[derive(Default, Debug, Clone)]
 pub struct Vertex {
     pub position: [f32; 3],
+    pub normal: [f32; 3],
     pub texture_coords: [f32; 2],
 }
-vulkano::impl_vertex!(Vertex, position, texture_coords);
+vulkano::impl_vertex!(Vertex, position, normal, texture_coords);
 
 pub type VertexBuffer = Arc<ImmutableBuffer<[Vertex]>>;
-pub type IndexBuffer = Arc<ImmutableBuffer<[u32]>>;
+
+// Meshes whose indices all fit in 16 bits use half the index bandwidth
+#[derive(Clone)]
+pub enum IndexBuffer {
+    U16(Arc<ImmutableBuffer<[u16]>>),
+    U32(Arc<ImmutableBuffer<[u32]>>),
+}
+
+pub type MeshBuffers = (VertexBuffer, IndexBuffer);
 
 pub mod vs {
     vulkano_shaders::shader! {
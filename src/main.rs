@@ -1,88 +1,85 @@
 mod event_loop;
-mod init;
-mod lib;
-
-use crate::event_loop::main_loop;
-use crate::init::*;
-use crate::lib::*;
+mod options;
+
+use crate::event_loop::{main_loop, HostState};
+use crate::options::Options;
+
+use vulkan_rust_tutorial::{
+    audio::AudioSystem,
+    camera::{Camera, CameraSettings},
+    clock::Clock,
+    focus::FocusState,
+    init::{create_debug_callback, create_instance, create_surface},
+    renderer::{Renderer, RendererSettings},
+    stats::FrameStats,
+    turntable::Turntable,
+    TITLE,
+};
 
-use std::time::Instant;
+use winit::event::ModifiersState;
 
-use vulkano::{
-    buffer::CpuBufferPool, command_buffer::DynamicState,
-    descriptor::descriptor_set::FixedSizeDescriptorSetsPool, sync::GpuFuture,
-};
+use log::error;
 
 use color_eyre::Result;
 
 pub fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let instance = create_instance()?;
-
-    let _debug_callback = create_debug_callback(&instance)?;
-
-    let (surface, event_loop) = create_surface(instance)?;
-
-    let (graphics_queue_family, present_queue_family) = pick_queues_families(&surface)?;
-
-    let (device, graphics_queue, present_queue) =
-        create_device(graphics_queue_family, present_queue_family)?;
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    let (mut swapchain, swapchain_images) = create_swapchain(
-        surface.clone(),
-        device.clone(),
-        graphics_queue.clone(),
-        present_queue.clone(),
-    )?;
+    let options = Options::from_args()?;
 
-    let (vertex_buffer, index_buffer) = create_buffers(graphics_queue.clone())?;
+    let validation = options.validation.unwrap_or(cfg!(debug_assertions));
+    let (instance, validation_enabled) = create_instance(validation)?;
 
-    let texture = load_texture(graphics_queue.clone())?;
+    let _debug_callback = create_debug_callback(&instance, validation_enabled)?;
 
-    let sampler = create_sampler(device.clone())?;
-
-    let render_pass = create_render_pass(device.clone(), swapchain.clone())?;
-
-    let pipeline = create_pipeline(render_pass.clone())?;
-
-    let mut dynamic_state = DynamicState::none();
-    update_dynamic_viewport(swapchain.clone(), &mut dynamic_state);
-
-    let mut framebuffers = create_framebuffers(swapchain_images, render_pass.clone())?;
-
-    let uniform_buffer = CpuBufferPool::<vs::ty::UniformBufferObject>::uniform_buffer(device);
-
-    let mut descriptor_pool =
-        FixedSizeDescriptorSetsPool::new(pipeline.descriptor_set_layout(0).unwrap().clone());
+    let (surface, event_loop) = create_surface(instance)?;
 
-    let mut swapchain_out_of_date = false;
-    let mut previous_frame_future: Option<Box<dyn GpuFuture>> = None;
-    let start_instant = Instant::now();
+    // The binary is just a winit host driving the same embedding API as examples/embedded.rs
+    let settings = RendererSettings {
+        extent: surface.window().inner_size().into(),
+        ..options.renderer_settings()?
+    };
+    let mut renderer = Renderer::with_surface(surface.clone(), &settings)?;
+
+    let mut host = HostState {
+        audio: AudioSystem::new(settings.asset_root.clone(), options.volume, options.mute),
+        camera: Camera::new(CameraSettings {
+            look_sensitivity: options.look_sensitivity,
+            invert_y: options.invert_y,
+            zoom_speed: options.zoom_speed,
+        }),
+        turntable: Turntable::new(
+            options.turntable_axis,
+            options.turntable_speed,
+            options.turntable_swing,
+            options.turntable_idle,
+            options.time_offset,
+        ),
+        clock: Clock::new(options.time_offset),
+        focus: FocusState::new(options.background_fps, options.pause_in_background),
+        stats: options
+            .frame_stats
+            .then(|| FrameStats::new(options.frame_stats_window)),
+        title: TITLE.to_owned(),
+        modifiers: ModifiersState::empty(),
+    };
+    if let Some(ambient) = &options.ambient {
+        host.audio.play_ambient(ambient);
+    }
 
     event_loop.run(move |event, _, control_flow| {
         main_loop(
             event,
             control_flow,
-            start_instant,
-            graphics_queue.clone(),
-            present_queue.clone(),
-            vertex_buffer.clone(),
-            index_buffer.clone(),
-            render_pass.clone(),
-            pipeline.clone(),
-            texture.clone(),
-            sampler.clone(),
-            &uniform_buffer,
-            &mut descriptor_pool,
-            &mut swapchain,
-            &mut dynamic_state,
-            &mut framebuffers,
-            &mut swapchain_out_of_date,
-            &mut previous_frame_future,
+            surface.window(),
+            &mut renderer,
+            &mut host,
         )
         .unwrap_or_else(|e| {
-            println!("\nError when running main loop: {e:?}\n");
+            error!("Error when running main loop: {e:?}");
+            host.audio.shutdown();
             std::process::exit(1);
         });
     });
@@ -0,0 +1,316 @@
+use crate::Vertex;
+
+use std::{collections::HashMap, str::FromStr};
+
+use nalgebra_glm as glm;
+
+// Faces whose normal is within ~10 degrees of an axis count as facing along it
+const AXIS_ALIGNMENT_THRESHOLD: f32 = 0.985;
+
+// Minimum ratio between the facing areas of both axes to trust the face normals over the bounding box
+const FACE_AREA_CONFIDENCE: f32 = 1.2;
+
+const TARGET_LARGEST_DIMENSION: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpAxis {
+    Y,
+    Z,
+}
+
+impl FromStr for UpAxis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "y" => Ok(UpAxis::Y),
+            "z" => Ok(UpAxis::Z),
+            _ => Err(format!("expected y, z or auto, got {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: glm::Vec3,
+    pub max: glm::Vec3,
+}
+
+impl Aabb {
+    pub fn from_positions(positions: &[f32]) -> Option<Self> {
+        let mut points = positions
+            .chunks_exact(3)
+            .map(|pos| glm::vec3(pos[0], pos[1], pos[2]));
+
+        let first = points.next()?;
+        Some(points.fold(
+            Aabb {
+                min: first,
+                max: first,
+            },
+            |aabb, point| Aabb {
+                min: glm::min2(&aabb.min, &point),
+                max: glm::max2(&aabb.max, &point),
+            },
+        ))
+    }
+
+    pub fn extent(&self) -> glm::Vec3 {
+        self.max - self.min
+    }
+}
+
+// Floors and roofs face up, so the axis collecting the most area of axis-aligned faces is assumed to be up.
+// When the faces don't clearly favor an axis, the flattest dimension of the bounding box is assumed to be up.
+pub fn guess_up_axis(positions: &[f32], indices: &[u32], aabb: &Aabb) -> UpAxis {
+    let (mut y_area, mut z_area) = (0.0, 0.0);
+
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| {
+            let i = 3 * index as usize;
+            glm::vec3(positions[i], positions[i + 1], positions[i + 2])
+        });
+
+        let cross = glm::cross(&(b - a), &(c - a));
+        let area = glm::length(&cross) / 2.0;
+        if area <= f32::EPSILON {
+            continue;
+        }
+
+        let normal = cross / (2.0 * area);
+        if normal.y.abs() > AXIS_ALIGNMENT_THRESHOLD {
+            y_area += area;
+        } else if normal.z.abs() > AXIS_ALIGNMENT_THRESHOLD {
+            z_area += area;
+        }
+    }
+
+    if y_area > FACE_AREA_CONFIDENCE * z_area {
+        UpAxis::Y
+    } else if z_area > FACE_AREA_CONFIDENCE * y_area {
+        UpAxis::Z
+    } else {
+        let extent = aabb.extent();
+        if extent.y < extent.z {
+            UpAxis::Y
+        } else {
+            UpAxis::Z
+        }
+    }
+}
+
+pub fn guess_unit_scale(aabb: &Aabb) -> f32 {
+    let largest_dimension = glm::comp_max(&aabb.extent());
+    if largest_dimension > f32::EPSILON {
+        TARGET_LARGEST_DIMENSION / largest_dimension
+    } else {
+        1.0
+    }
+}
+
+// The renderer is Z-up, so Y-up models are rotated by 90 degrees around X
+pub fn correction_transform(up_axis: UpAxis, unit_scale: f32) -> glm::Mat4 {
+    let rotation = match up_axis {
+        UpAxis::Y => glm::rotate_x(&glm::identity(), f32::to_radians(90.0)),
+        UpAxis::Z => glm::identity(),
+    };
+    glm::scale(&rotation, &glm::vec3(unit_scale, unit_scale, unit_scale))
+}
+
+// Models without normals are flat shaded, the face normal splits vertices shared between faces
+pub fn deduplicate_vertices(
+    positions: &[f32],
+    normals: &[f32],
+    texcoords: &[f32],
+    indices: &[u32],
+) -> (Vec<Vertex>, Vec<u32>) {
+    //
+    let mut unique_vertices = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut new_indices = Vec::with_capacity(indices.len());
+
+    for triangle in indices.chunks_exact(3) {
+        let face_normal = face_normal(positions, triangle);
+
+        for &index in triangle {
+            let index = index as usize;
+            let position = [
+                positions[3 * index],
+                positions[3 * index + 1],
+                positions[3 * index + 2],
+            ];
+            let normal = match normals.get(3 * index..3 * index + 3) {
+                Some(n) => [n[0], n[1], n[2]],
+                None => face_normal,
+            };
+            let texture_coords = match texcoords.get(2 * index..2 * index + 2) {
+                Some(tex) => [tex[0], 1.0 - tex[1]],
+                None => [0.0, 0.0],
+            };
+
+            // Keyed on the bit patterns, since floats are neither Eq nor Hash
+            let key = (
+                position.map(f32::to_bits),
+                normal.map(f32::to_bits),
+                texture_coords.map(f32::to_bits),
+            );
+
+            new_indices.push(*unique_vertices.entry(key).or_insert_with(|| {
+                vertices.push(Vertex {
+                    position,
+                    normal,
+                    texture_coords,
+                });
+                (vertices.len() - 1) as u32
+            }));
+        }
+    }
+
+    (vertices, new_indices)
+}
+
+fn face_normal(positions: &[f32], triangle: &[u32]) -> [f32; 3] {
+    let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| {
+        let i = 3 * index as usize;
+        glm::vec3(positions[i], positions[i + 1], positions[i + 2])
+    });
+
+    // Degenerate triangles get an arbitrary unit normal instead of a NaN one
+    let cross = glm::cross(&(b - a), &(c - a));
+    if glm::length(&cross) > f32::EPSILON {
+        glm::normalize(&cross).into()
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Corners of an axis-aligned box, without faces so only the bounding box drives the guess
+    fn box_corners(extent: [f32; 3]) -> Vec<f32> {
+        let mut positions = Vec::new();
+        for x in [0.0, extent[0]] {
+            for y in [0.0, extent[1]] {
+                for z in [0.0, extent[2]] {
+                    positions.extend([x, y, z]);
+                }
+            }
+        }
+        positions
+    }
+
+    fn guess_from_corners(extent: [f32; 3]) -> UpAxis {
+        let positions = box_corners(extent);
+        let aabb = Aabb::from_positions(&positions).unwrap();
+        guess_up_axis(&positions, &[], &aabb)
+    }
+
+    fn assert_close(a: glm::Vec4, b: glm::Vec4) {
+        assert!(glm::distance(&a, &b) < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn flat_z_up_model_is_detected() {
+        assert_eq!(guess_from_corners([2.0, 1.5, 0.3]), UpAxis::Z);
+    }
+
+    #[test]
+    fn flat_y_up_model_is_detected() {
+        assert_eq!(guess_from_corners([2.0, 0.3, 1.5]), UpAxis::Y);
+    }
+
+    #[test]
+    fn horizontal_faces_override_the_bounding_box() {
+        // A tall Y-up floor tile: the box alone would call Z up
+        let positions = [
+            0.0, 0.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            1.0, 0.0, 1.0, //
+            0.0, 0.0, 1.0, //
+            0.0, 1.5, 0.0,
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+        let aabb = Aabb::from_positions(&positions).unwrap();
+
+        assert_eq!(guess_up_axis(&positions, &indices, &aabb), UpAxis::Y);
+    }
+
+    #[test]
+    fn centimetre_model_is_scaled_to_the_target_size() {
+        // A 2 m tall Y-up model exported in centimetres
+        let positions = box_corners([50.0, 200.0, 30.0]);
+        let aabb = Aabb::from_positions(&positions).unwrap();
+
+        let unit_scale = guess_unit_scale(&aabb);
+        assert!((unit_scale - 0.01).abs() < 1e-6);
+
+        let top = correction_transform(UpAxis::Y, unit_scale) * glm::vec4(0.0, 200.0, 0.0, 1.0);
+        assert_close(top, glm::vec4(0.0, 0.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn z_up_model_is_only_scaled() {
+        let point = correction_transform(UpAxis::Z, 0.5) * glm::vec4(1.0, 2.0, 4.0, 1.0);
+        assert_close(point, glm::vec4(0.5, 1.0, 2.0, 1.0));
+    }
+
+    // Two triangles sharing an edge, in the XY plane
+    const QUAD_POSITIONS: [f32; 12] = [
+        0.0, 0.0, 0.0, //
+        1.0, 0.0, 0.0, //
+        1.0, 1.0, 0.0, //
+        0.0, 1.0, 0.0,
+    ];
+    const QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+    #[test]
+    fn shared_quad_vertices_are_deduplicated() {
+        let normals = [0.0, 0.0, 1.0].repeat(4);
+        let texcoords = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+
+        let (vertices, indices) =
+            deduplicate_vertices(&QUAD_POSITIONS, &normals, &texcoords, &QUAD_INDICES);
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices, QUAD_INDICES);
+        assert_eq!(vertices[2].texture_coords, [1.0, 0.0]);
+    }
+
+    #[test]
+    fn missing_normals_fall_back_to_the_face_normal() {
+        let (vertices, indices) = deduplicate_vertices(&QUAD_POSITIONS, &[], &[], &QUAD_INDICES);
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 6);
+        assert!(vertices
+            .iter()
+            .all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn flat_shaded_faces_split_shared_vertices() {
+        // Two faces of a cube edge, the shared vertices get one normal per face
+        let positions = [
+            0.0, 0.0, 0.0, //
+            1.0, 0.0, 0.0, //
+            1.0, 0.0, 1.0, //
+            0.0, 1.0, 0.0,
+        ];
+        let indices = [0, 1, 2, 1, 0, 3];
+
+        let (vertices, indices) = deduplicate_vertices(&positions, &[], &[], &indices);
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(vertices[0].normal, [0.0, -1.0, 0.0]);
+        assert_eq!(vertices[3].normal, [0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn degenerate_triangles_get_a_unit_normal() {
+        let positions = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0];
+        assert_eq!(face_normal(&positions, &[0, 1, 2]), [0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,393 @@
+use vulkan_rust_tutorial::{
+    assets::AssetRoot,
+    color::SrgbColor,
+    letterbox::Letterbox,
+    mesh::UpAxis,
+    pipeline::Winding,
+    presentation::{Latency, PresentModePreference},
+    renderer::RendererSettings,
+    turntable::Axis,
+};
+
+use nalgebra_glm as glm;
+
+use std::{fmt::Display, path::PathBuf, str::FromStr, time::Duration};
+
+use color_eyre::{eyre::eyre, Result};
+
+const VALIDATION_ENV: &str = "VULKAN_VALIDATION";
+
+const USAGE: &str = "\
+Usage: vulkan-rust-tutorial [OPTIONS]
+
+Options:
+  --validation <on|off>        Enable the Vulkan validation layer, also --validation=on|off.
+                               Overrides VULKAN_VALIDATION=on|off, which overrides the
+                               default of on in debug builds
+  --gpu <INDEX|NAME>           Physical device to use instead of the best one
+  --model <PATH>               OBJ model to display, or builtin:cube
+  --texture <PATH>             Texture image, or builtin:white / builtin:checker / builtin:uv-test
+  --asset-root <DIR|ZIP>       Directory or archive searched first for relative asset paths
+  --asset-archive <ZIP>        Archive searched after --asset-root, whose files override it
+  --up-axis <y|z|auto>         Up axis of the model [default: z]
+  --unit-scale <SCALE|auto>    Scale applied to the model [default: 1]
+  --letterbox <PRESET>         window, 4:3, 16:9, 21:9 or 1:1, cycled with L [default: window]
+  --front-face <ccw|cw>        Winding of front faces, culling toggled with C [default: ccw]
+  --samples <COUNT>            MSAA sample count [default: 1]
+  --clear-color <HEX>          Background color as an sRGB hex code [default: #000000]
+  --latency <low|smooth>       Presentation latency profile
+  --present-mode <MODE>        fifo, mailbox or immediate, cycled with V
+  --frames-in-flight <COUNT>   Frames recorded ahead of the GPU, overrides the --latency profile
+  --time-offset <SECONDS>      Initial value of the animation clock, restored with R [default: 0]
+  --background-fps <FPS>       Frame rate when the window is unfocused, 0 to stop [default: 10]
+  --pause-in-background        Pause the animation clock when the window is unfocused
+  --look-sensitivity <DEG>     Camera rotation per pixel dragged [default: 0.2865]
+  --invert-y                   Invert vertical camera dragging
+  --zoom-speed <FRACTION>      Distance change per scroll line, between 0 and 1 [default: 0.1]
+  --turntable-axis <AXIS>      x, y, z or a vector like 0,0,1 [default: z]
+  --turntable-speed <DEG/S>    Turntable rotation speed [default: 90]
+  --turntable-swing <DEG>      Oscillation amplitude, 0 for a full spin [default: 0]
+  --turntable-idle <SECONDS>   Delay before the turntable resumes after input [default: 0]
+  --frame-stats <on|off>       Show frame rate and frame times in the window title [default: on]
+  --frame-stats-window <COUNT> Number of frames the stats are computed over [default: 120]
+  --watch-shaders              Reload shaders when they change, needs the hot-reload feature
+  --volume <0-1>               Sound volume, needs the audio feature [default: 1]
+  --mute                       Start with sounds muted, toggled with M
+  --ambient <PATH>             Ambient track looped in the background
+  -h, --help                   Print this help";
+
+#[derive(Debug)]
+pub struct Options {
+    pub time_offset: f64,
+    pub background_fps: f64,
+    pub pause_in_background: bool,
+    pub validation: Option<bool>,
+    pub gpu: Option<String>,
+    pub asset_root: Option<PathBuf>,
+    pub asset_archive: Option<PathBuf>,
+    pub model: Option<String>,
+    pub texture: Option<String>,
+    pub up_axis: Option<UpAxis>,
+    pub unit_scale: Option<f32>,
+    pub latency: Option<Latency>,
+    pub present_mode: Option<PresentModePreference>,
+    pub frames_in_flight: Option<usize>,
+    pub watch_shaders: bool,
+    pub volume: f32,
+    pub mute: bool,
+    pub ambient: Option<String>,
+    pub frame_stats: bool,
+    pub frame_stats_window: usize,
+    pub samples: u32,
+    pub front_face: Winding,
+    pub letterbox: Letterbox,
+    pub clear_color: SrgbColor,
+    pub look_sensitivity: Option<f32>,
+    pub invert_y: bool,
+    pub zoom_speed: Option<f32>,
+    pub turntable_axis: Axis,
+    pub turntable_speed: f64,
+    pub turntable_swing: f64,
+    pub turntable_idle: f64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            time_offset: 0.0,
+            background_fps: 10.0,
+            pause_in_background: false,
+            validation: None,
+            gpu: None,
+            asset_root: None,
+            asset_archive: None,
+            model: None,
+            texture: None,
+            up_axis: Some(UpAxis::Z),
+            unit_scale: Some(1.0),
+            latency: None,
+            present_mode: None,
+            frames_in_flight: None,
+            watch_shaders: false,
+            volume: 1.0,
+            mute: false,
+            ambient: None,
+            frame_stats: true,
+            frame_stats_window: 120,
+            samples: 1,
+            front_face: Winding::CounterClockwise,
+            letterbox: Letterbox::Window,
+            clear_color: SrgbColor::BLACK,
+            look_sensitivity: None,
+            invert_y: false,
+            zoom_speed: None,
+            turntable_axis: Axis(glm::vec3(0.0, 0.0, 1.0)),
+            turntable_speed: 90.0,
+            turntable_swing: 0.0,
+            turntable_idle: 0.0,
+        }
+    }
+}
+
+impl Options {
+    pub fn from_args() -> Result<Self> {
+        Self::parse(std::env::args().skip(1), std::env::var(VALIDATION_ENV).ok())
+    }
+
+    fn parse(
+        mut args: impl Iterator<Item = String>,
+        validation_env: Option<String>,
+    ) -> Result<Self> {
+        let mut options = Options::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--time-offset" => options.time_offset = parse_finite(&arg, args.next())?,
+                "--background-fps" => options.background_fps = parse_frame_rate(&arg, args.next())?,
+                "--pause-in-background" => options.pause_in_background = true,
+                "--validation" => options.validation = Some(parse_switch(&arg, args.next())?),
+                _ if arg.starts_with("--validation=") => {
+                    let value = arg.split_once('=').map(|(_, value)| value.to_owned());
+                    options.validation = Some(parse_switch("--validation", value)?)
+                }
+                "--gpu" => options.gpu = Some(parse_value(&arg, args.next())?),
+                "--asset-root" => options.asset_root = Some(parse_value(&arg, args.next())?),
+                "--asset-archive" => options.asset_archive = Some(parse_value(&arg, args.next())?),
+                "--model" => options.model = Some(parse_value(&arg, args.next())?),
+                "--texture" => options.texture = Some(parse_value(&arg, args.next())?),
+                "--up-axis" => options.up_axis = parse_or_auto(&arg, args.next())?,
+                "--unit-scale" => options.unit_scale = parse_or_auto(&arg, args.next())?,
+                "--latency" => options.latency = Some(parse_value(&arg, args.next())?),
+                "--present-mode" => options.present_mode = Some(parse_value(&arg, args.next())?),
+                "--frames-in-flight" => {
+                    options.frames_in_flight = Some(parse_value(&arg, args.next())?)
+                }
+                "--samples" => options.samples = parse_value(&arg, args.next())?,
+                "--front-face" => options.front_face = parse_value(&arg, args.next())?,
+                "--letterbox" => options.letterbox = parse_value(&arg, args.next())?,
+                "--clear-color" => options.clear_color = parse_value(&arg, args.next())?,
+                "--look-sensitivity" => {
+                    options.look_sensitivity = Some(parse_value(&arg, args.next())?)
+                }
+                "--invert-y" => options.invert_y = true,
+                "--zoom-speed" => options.zoom_speed = Some(parse_fraction(&arg, args.next())?),
+                "--turntable-axis" => options.turntable_axis = parse_value(&arg, args.next())?,
+                "--turntable-speed" => options.turntable_speed = parse_value(&arg, args.next())?,
+                "--turntable-swing" => options.turntable_swing = parse_value(&arg, args.next())?,
+                "--turntable-idle" => options.turntable_idle = parse_value(&arg, args.next())?,
+                "--watch-shaders" => options.watch_shaders = true,
+                "--volume" => options.volume = parse_volume(&arg, args.next())?,
+                "--mute" => options.mute = true,
+                "--ambient" => options.ambient = Some(parse_value(&arg, args.next())?),
+                "--frame-stats" => options.frame_stats = parse_switch(&arg, args.next())?,
+                "--frame-stats-window" => {
+                    options.frame_stats_window = parse_value(&arg, args.next())?
+                }
+                "-h" | "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ => return Err(eyre!("unknown argument: {arg}\n\n{USAGE}")),
+            }
+        }
+
+        // The command line wins over the environment, which wins over the build default
+        if options.validation.is_none() {
+            if let Some(value) = validation_env {
+                options.validation = Some(parse_switch(VALIDATION_ENV, Some(value))?);
+            }
+        }
+
+        Ok(options)
+    }
+
+    pub fn renderer_settings(&self) -> Result<RendererSettings> {
+        Ok(RendererSettings {
+            asset_root: AssetRoot::new(self.asset_root.clone(), self.asset_archive.clone())?,
+            gpu: self.gpu.clone(),
+            model: self.model.clone(),
+            texture: self.texture.clone(),
+            up_axis: self.up_axis,
+            unit_scale: self.unit_scale,
+            latency: self.latency,
+            present_mode: self.present_mode,
+            frames_in_flight: self.frames_in_flight,
+            watch_shaders: self.watch_shaders,
+            samples: self.samples,
+            front_face: self.front_face,
+            letterbox: self.letterbox,
+            clear_color: self.clear_color,
+            ..RendererSettings::default()
+        })
+    }
+}
+
+fn parse_value<T>(flag: &str, value: Option<String>) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = value.ok_or_else(|| eyre!("missing value for {flag}"))?;
+    value
+        .parse()
+        .map_err(|e| eyre!("invalid value for {flag}: {value} ({e})"))
+}
+
+// A zoom step of 0 or 1 would never move the camera or collapse it onto its target
+fn parse_fraction(flag: &str, value: Option<String>) -> Result<f32> {
+    let fraction = parse_value(flag, value)?;
+    if 0.0 < fraction && fraction < 1.0 {
+        Ok(fraction)
+    } else {
+        Err(eyre!(
+            "invalid value for {flag}: {fraction} (expected a fraction between 0 and 1)"
+        ))
+    }
+}
+
+// NaN or infinite values would poison the clock and the turntable angle for good
+fn parse_finite(flag: &str, value: Option<String>) -> Result<f64> {
+    let number: f64 = parse_value(flag, value)?;
+    if number.is_finite() {
+        Ok(number)
+    } else {
+        Err(eyre!(
+            "invalid value for {flag}: {number} (expected a finite number)"
+        ))
+    }
+}
+
+// 0 stops rendering in the background. Rates so low that their frame time doesn't fit in a
+// Duration would panic when the window loses focus
+fn parse_frame_rate(flag: &str, value: Option<String>) -> Result<f64> {
+    let fps: f64 = parse_value(flag, value)?;
+    if fps == 0.0 || (fps.is_finite() && Duration::try_from_secs_f64(1.0 / fps).is_ok()) {
+        Ok(fps)
+    } else {
+        Err(eyre!(
+            "invalid value for {flag}: {fps} (expected 0 or a positive frame rate)"
+        ))
+    }
+}
+
+fn parse_volume(flag: &str, value: Option<String>) -> Result<f32> {
+    let volume = parse_value(flag, value)?;
+    if (0.0..=1.0).contains(&volume) {
+        Ok(volume)
+    } else {
+        Err(eyre!(
+            "invalid value for {flag}: {volume} (expected a volume between 0 and 1)"
+        ))
+    }
+}
+
+fn parse_switch(flag: &str, value: Option<String>) -> Result<bool> {
+    match value.as_deref() {
+        Some("on") => Ok(true),
+        Some("off") => Ok(false),
+        Some(value) => Err(eyre!(
+            "invalid value for {flag}: {value} (expected on or off)"
+        )),
+        None => Err(eyre!("missing value for {flag}")),
+    }
+}
+
+fn parse_or_auto<T>(flag: &str, value: Option<String>) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match value.as_deref() {
+        Some("auto") => Ok(None),
+        _ => parse_value(flag, value).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str], validation_env: Option<&str>) -> Result<Options> {
+        Options::parse(
+            args.iter().map(|arg| arg.to_string()),
+            validation_env.map(str::to_owned),
+        )
+    }
+
+    fn validation(args: &[&str], validation_env: Option<&str>) -> Option<bool> {
+        parse(args, validation_env).unwrap().validation
+    }
+
+    #[test]
+    fn validation_flag_accepts_both_forms() {
+        assert_eq!(validation(&["--validation", "on"], None), Some(true));
+        assert_eq!(validation(&["--validation=off"], None), Some(false));
+        assert_eq!(validation(&[], None), None);
+
+        assert!(parse(&["--validation=yes"], None).is_err());
+        assert!(parse(&["--validation="], None).is_err());
+    }
+
+    #[test]
+    fn validation_flag_overrides_the_environment() {
+        assert_eq!(validation(&[], Some("on")), Some(true));
+        assert_eq!(validation(&[], Some("off")), Some(false));
+        assert_eq!(validation(&["--validation=on"], Some("off")), Some(true));
+        assert_eq!(
+            validation(&["--validation", "off"], Some("on")),
+            Some(false)
+        );
+
+        assert!(parse(&[], Some("1")).is_err());
+        assert!(parse(&["--validation=on"], Some("1")).is_ok());
+    }
+
+    fn fraction(value: &str) -> Result<f32> {
+        parse_fraction("--zoom-speed", Some(value.to_owned()))
+    }
+
+    #[test]
+    fn fractions_must_be_strictly_between_0_and_1() {
+        assert_eq!(fraction("0.1").unwrap(), 0.1);
+        assert_eq!(fraction("0.99").unwrap(), 0.99);
+
+        for value in ["0", "1", "-0.5", "1.5", "NaN", "inf", "-inf", "fast"] {
+            assert!(fraction(value).is_err(), "{value} was accepted");
+        }
+    }
+
+    #[test]
+    fn time_offsets_must_be_finite() {
+        let offset = |value: &str| parse_finite("--time-offset", Some(value.to_owned()));
+
+        assert_eq!(offset("-12.5").unwrap(), -12.5);
+        for value in ["NaN", "inf", "-inf"] {
+            assert!(offset(value).is_err(), "{value} was accepted");
+        }
+    }
+
+    #[test]
+    fn background_frame_rates_must_give_a_frame_time() {
+        let fps = |value: &str| parse_frame_rate("--background-fps", Some(value.to_owned()));
+
+        assert_eq!(fps("0").unwrap(), 0.0);
+        assert_eq!(fps("0.5").unwrap(), 0.5);
+        assert_eq!(fps("144").unwrap(), 144.0);
+
+        for value in ["-1", "NaN", "inf", "1e-320", "1e-30"] {
+            assert!(fps(value).is_err(), "{value} was accepted");
+        }
+    }
+
+    #[test]
+    fn volumes_must_be_between_0_and_1() {
+        let volume = |value: &str| parse_volume("--volume", Some(value.to_owned()));
+
+        assert_eq!(volume("0").unwrap(), 0.0);
+        assert_eq!(volume("1").unwrap(), 1.0);
+
+        for value in ["-0.1", "1.5", "NaN", "inf"] {
+            assert!(volume(value).is_err(), "{value} was accepted");
+        }
+    }
+}
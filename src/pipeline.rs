@@ -0,0 +1,48 @@
+use std::str::FromStr;
+
+use vulkano::pipeline::raster::{CullMode, FrontFace, PolygonMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    CounterClockwise,
+    Clockwise,
+}
+
+impl Winding {
+    pub fn front_face(self) -> FrontFace {
+        match self {
+            Winding::CounterClockwise => FrontFace::CounterClockwise,
+            Winding::Clockwise => FrontFace::Clockwise,
+        }
+    }
+}
+
+impl FromStr for Winding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ccw" => Ok(Winding::CounterClockwise),
+            "cw" => Ok(Winding::Clockwise),
+            _ => Err(format!("expected ccw or cw, got {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineOptions {
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+    pub polygon_mode: PolygonMode,
+}
+
+// The projection flips Y, so the counter-clockwise faces of the model end up facing the camera
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        Self {
+            cull_mode: CullMode::Back,
+            front_face: FrontFace::CounterClockwise,
+            polygon_mode: PolygonMode::Fill,
+        }
+    }
+}
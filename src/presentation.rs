@@ -0,0 +1,371 @@
+use std::str::FromStr;
+
+use vulkano::{
+    image::ImageUsage,
+    swapchain::{Capabilities, PresentMode},
+};
+
+use log::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Latency {
+    Low,
+    Smooth,
+}
+
+impl FromStr for Latency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Latency::Low),
+            "smooth" => Ok(Latency::Smooth),
+            _ => Err(format!("expected low or smooth, got {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentModePreference {
+    Fifo,
+    Mailbox,
+    Immediate,
+}
+
+impl PresentModePreference {
+    pub fn from_present_mode(present_mode: PresentMode) -> Self {
+        match present_mode {
+            PresentMode::Mailbox => PresentModePreference::Mailbox,
+            PresentMode::Immediate => PresentModePreference::Immediate,
+            _ => PresentModePreference::Fifo,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            PresentModePreference::Fifo => PresentModePreference::Mailbox,
+            PresentModePreference::Mailbox => PresentModePreference::Immediate,
+            PresentModePreference::Immediate => PresentModePreference::Fifo,
+        }
+    }
+
+    fn is_supported(self, limits: &PresentationLimits) -> bool {
+        match self {
+            PresentModePreference::Fifo => true,
+            PresentModePreference::Mailbox => limits.mailbox,
+            PresentModePreference::Immediate => limits.immediate,
+        }
+    }
+
+    fn present_mode(self) -> PresentMode {
+        match self {
+            PresentModePreference::Fifo => PresentMode::Fifo,
+            PresentModePreference::Mailbox => PresentMode::Mailbox,
+            PresentModePreference::Immediate => PresentMode::Immediate,
+        }
+    }
+}
+
+impl FromStr for PresentModePreference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fifo" => Ok(PresentModePreference::Fifo),
+            "mailbox" => Ok(PresentModePreference::Mailbox),
+            "immediate" => Ok(PresentModePreference::Immediate),
+            _ => Err(format!("expected fifo, mailbox or immediate, got {s}")),
+        }
+    }
+}
+
+// The parts of the surface capabilities a presentation profile depends on, Fifo is always supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresentationLimits {
+    pub min_image_count: u32,
+    pub max_image_count: Option<u32>,
+    pub mailbox: bool,
+    pub immediate: bool,
+}
+
+impl From<&Capabilities> for PresentationLimits {
+    fn from(capabilities: &Capabilities) -> Self {
+        Self {
+            min_image_count: capabilities.min_image_count,
+            max_image_count: capabilities.max_image_count,
+            mailbox: capabilities.present_modes.mailbox,
+            immediate: capabilities.present_modes.immediate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresentationProfile {
+    pub image_count: u32,
+    pub present_mode: PresentMode,
+    pub frames_in_flight: usize,
+}
+
+impl PresentationProfile {
+    pub fn resolve(
+        latency: Option<Latency>,
+        preference: Option<PresentModePreference>,
+        frames_in_flight: Option<usize>,
+        limits: &PresentationLimits,
+    ) -> Self {
+        //
+        let (image_count, present_mode, profile_frames_in_flight) = match latency {
+            None => {
+                let present_mode = if limits.mailbox {
+                    PresentMode::Mailbox
+                } else if limits.immediate {
+                    PresentMode::Immediate
+                } else {
+                    PresentMode::Fifo
+                };
+                (limits.min_image_count + 1, present_mode, 2)
+            }
+            Some(Latency::Low) => {
+                let present_mode = if limits.mailbox {
+                    PresentMode::Mailbox
+                } else {
+                    PresentMode::Fifo
+                };
+                (2, present_mode, 1)
+            }
+            Some(Latency::Smooth) => (3, PresentMode::Fifo, 2),
+        };
+
+        // An explicit present mode overrides the one picked for the latency profile
+        let present_mode = match preference {
+            Some(preference) if preference.is_supported(limits) => preference.present_mode(),
+            Some(preference) => {
+                warn!("{preference:?} present mode is not supported, falling back to Fifo");
+                PresentMode::Fifo
+            }
+            None => present_mode,
+        };
+
+        let image_count = image_count
+            .max(limits.min_image_count)
+            .min(limits.max_image_count.unwrap_or(u32::MAX));
+
+        // An explicit count overrides the profile too, at least one frame is always recorded
+        let frames_in_flight = frames_in_flight.unwrap_or(profile_frames_in_flight).max(1);
+
+        Self {
+            image_count,
+            present_mode,
+            frames_in_flight,
+        }
+    }
+}
+
+// Optional features that need swapchain image usage on top of rendering into the images
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapchainFeatures {
+    pub screenshots: bool,
+}
+
+// Features whose usage bits the surface doesn't support are disabled, color attachment usage is
+// always supported
+pub fn negotiate_usage(
+    requested: SwapchainFeatures,
+    supported: ImageUsage,
+) -> (ImageUsage, SwapchainFeatures) {
+    //
+    let enabled = SwapchainFeatures {
+        screenshots: requested.screenshots && supported.transfer_source,
+    };
+
+    let usage = ImageUsage {
+        color_attachment: true,
+        transfer_source: enabled.screenshots,
+        ..ImageUsage::none()
+    };
+
+    (usage, enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAILBOX_AND_FIFO: PresentationLimits = PresentationLimits {
+        min_image_count: 2,
+        max_image_count: Some(8),
+        mailbox: true,
+        immediate: false,
+    };
+
+    const FIFO_ONLY: PresentationLimits = PresentationLimits {
+        min_image_count: 2,
+        max_image_count: None,
+        mailbox: false,
+        immediate: false,
+    };
+
+    const EXACTLY_THREE_IMAGES: PresentationLimits = PresentationLimits {
+        min_image_count: 3,
+        max_image_count: Some(3),
+        mailbox: true,
+        immediate: true,
+    };
+
+    fn resolve(
+        latency: Option<Latency>,
+        preference: Option<PresentModePreference>,
+        limits: &PresentationLimits,
+    ) -> (u32, PresentMode) {
+        //
+        let profile = PresentationProfile::resolve(latency, preference, None, limits);
+        (profile.image_count, profile.present_mode)
+    }
+
+    #[test]
+    fn mailbox_and_fifo_surface() {
+        let limits = &MAILBOX_AND_FIFO;
+
+        assert_eq!(resolve(None, None, limits), (3, PresentMode::Mailbox));
+        assert_eq!(
+            resolve(Some(Latency::Low), None, limits),
+            (2, PresentMode::Mailbox)
+        );
+        assert_eq!(
+            resolve(Some(Latency::Smooth), None, limits),
+            (3, PresentMode::Fifo)
+        );
+
+        let low = Some(Latency::Low);
+        let smooth = Some(Latency::Smooth);
+        assert_eq!(
+            resolve(low, Some(PresentModePreference::Fifo), limits),
+            (2, PresentMode::Fifo)
+        );
+        assert_eq!(
+            resolve(smooth, Some(PresentModePreference::Mailbox), limits),
+            (3, PresentMode::Mailbox)
+        );
+        assert_eq!(
+            resolve(low, Some(PresentModePreference::Immediate), limits),
+            (2, PresentMode::Fifo)
+        );
+    }
+
+    #[test]
+    fn fifo_only_surface() {
+        let limits = &FIFO_ONLY;
+
+        assert_eq!(resolve(None, None, limits), (3, PresentMode::Fifo));
+        assert_eq!(
+            resolve(Some(Latency::Low), None, limits),
+            (2, PresentMode::Fifo)
+        );
+        assert_eq!(
+            resolve(Some(Latency::Smooth), None, limits),
+            (3, PresentMode::Fifo)
+        );
+
+        for preference in [
+            PresentModePreference::Fifo,
+            PresentModePreference::Mailbox,
+            PresentModePreference::Immediate,
+        ] {
+            assert_eq!(
+                resolve(Some(Latency::Low), Some(preference), limits),
+                (2, PresentMode::Fifo)
+            );
+            assert_eq!(
+                resolve(Some(Latency::Smooth), Some(preference), limits),
+                (3, PresentMode::Fifo)
+            );
+        }
+    }
+
+    #[test]
+    fn image_count_is_clamped_to_the_surface_limits() {
+        let limits = &EXACTLY_THREE_IMAGES;
+
+        assert_eq!(resolve(None, None, limits), (3, PresentMode::Mailbox));
+        assert_eq!(
+            resolve(Some(Latency::Low), None, limits),
+            (3, PresentMode::Mailbox)
+        );
+        assert_eq!(
+            resolve(Some(Latency::Smooth), None, limits),
+            (3, PresentMode::Fifo)
+        );
+        assert_eq!(
+            resolve(
+                Some(Latency::Smooth),
+                Some(PresentModePreference::Immediate),
+                limits
+            ),
+            (3, PresentMode::Immediate)
+        );
+
+        let two_images = PresentationLimits {
+            min_image_count: 1,
+            max_image_count: Some(2),
+            ..FIFO_ONLY
+        };
+        assert_eq!(
+            resolve(Some(Latency::Smooth), None, &two_images),
+            (2, PresentMode::Fifo)
+        );
+    }
+
+    #[test]
+    fn explicit_frames_in_flight_override_the_profile() {
+        let frames_in_flight = |latency, explicit| {
+            PresentationProfile::resolve(latency, None, explicit, &MAILBOX_AND_FIFO)
+                .frames_in_flight
+        };
+
+        assert_eq!(frames_in_flight(None, None), 2);
+        assert_eq!(frames_in_flight(Some(Latency::Low), None), 1);
+        assert_eq!(frames_in_flight(Some(Latency::Smooth), None), 2);
+
+        assert_eq!(frames_in_flight(None, Some(3)), 3);
+        assert_eq!(frames_in_flight(Some(Latency::Low), Some(3)), 3);
+        assert_eq!(frames_in_flight(Some(Latency::Smooth), Some(1)), 1);
+        assert_eq!(frames_in_flight(Some(Latency::Low), Some(0)), 1);
+    }
+
+    fn supported_usage(transfer_source: bool) -> ImageUsage {
+        ImageUsage {
+            color_attachment: true,
+            transfer_source,
+            ..ImageUsage::none()
+        }
+    }
+
+    #[test]
+    fn supported_features_are_enabled() {
+        let requested = SwapchainFeatures { screenshots: true };
+        let (usage, enabled) = negotiate_usage(requested, supported_usage(true));
+
+        assert!(usage.color_attachment);
+        assert!(usage.transfer_source);
+        assert_eq!(enabled, requested);
+    }
+
+    #[test]
+    fn unsupported_features_are_disabled() {
+        let requested = SwapchainFeatures { screenshots: true };
+        let (usage, enabled) = negotiate_usage(requested, supported_usage(false));
+
+        assert!(usage.color_attachment);
+        assert!(!usage.transfer_source);
+        assert!(!enabled.screenshots);
+    }
+
+    #[test]
+    fn unrequested_usage_is_not_added() {
+        let requested = SwapchainFeatures { screenshots: false };
+        let (usage, enabled) = negotiate_usage(requested, supported_usage(true));
+
+        assert_eq!(usage, supported_usage(false));
+        assert!(!enabled.screenshots);
+    }
+}
@@ -0,0 +1,785 @@
+use crate::assets::AssetRoot;
+use crate::clock::FrameTime;
+use crate::color::{self, LinearColor, SrgbColor};
+use crate::error::Error;
+use crate::init::*;
+use crate::letterbox::Letterbox;
+use crate::mesh::UpAxis;
+use crate::pipeline::{PipelineOptions, Winding};
+use crate::presentation::{negotiate_usage, Latency, PresentModePreference, SwapchainFeatures};
+use crate::screenshot::{self, PendingScreenshot};
+use crate::shaders::Shaders;
+#[cfg(feature = "hot-reload")]
+use crate::shaders::{load_shaders, ShaderWatcher};
+use crate::{vs, IndexBuffer, MeshBuffers, HEIGHT, WIDTH};
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use vulkano::{
+    buffer::CpuBufferPool,
+    command_buffer::{AutoCommandBufferBuilder, DynamicState, SubpassContents},
+    descriptor::{descriptor_set::FixedSizeDescriptorSetsPool, DescriptorSet},
+    device::Queue,
+    format::{ClearValue, Format, FormatTy},
+    framebuffer::{FramebufferAbstract, RenderPassAbstract},
+    image::{view::ImageView, ImageUsage, ImmutableImage, SwapchainImage},
+    pipeline::{raster::CullMode, GraphicsPipelineAbstract},
+    sampler::Sampler,
+    swapchain::{self, AcquireError, Surface, Swapchain, SwapchainCreationError},
+    sync::{self, FenceSignalFuture, FlushError, GpuFuture},
+};
+
+use nalgebra_glm as glm;
+
+use log::{error, info, warn};
+
+use color_eyre::{eyre::eyre, Result};
+
+const SUBOPTIMAL_RECREATE_INTERVAL: u32 = 120;
+
+const LIGHT_DIRECTION: [f32; 4] = [-0.4, -0.2, -1.0, 0.0];
+const LIGHT_COLOR: LinearColor = LinearColor::WHITE;
+// Linear intensity, added to the diffuse term before it scales the linear texture color
+const AMBIENT_LIGHT: f32 = 0.15;
+
+// The command buffer kept alive by a fence also owns that frame's descriptor set and uniform
+// buffer chunk, so waiting on a slot's fence before reusing it bounds both allocations
+type FrameFence = Arc<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>>;
+
+// The renderer never creates a window or touches the event loop, the host owns the surface and
+// reports its size changes through Renderer::resize
+#[derive(Debug, Clone)]
+pub struct RendererSettings {
+    pub asset_root: AssetRoot,
+    pub extent: [u32; 2],
+    pub gpu: Option<String>,
+    pub model: Option<String>,
+    pub texture: Option<String>,
+    pub up_axis: Option<UpAxis>,
+    pub unit_scale: Option<f32>,
+    pub latency: Option<Latency>,
+    pub present_mode: Option<PresentModePreference>,
+    pub frames_in_flight: Option<usize>,
+    pub watch_shaders: bool,
+    pub samples: u32,
+    pub front_face: Winding,
+    pub letterbox: Letterbox,
+    pub clear_color: SrgbColor,
+}
+
+impl Default for RendererSettings {
+    fn default() -> Self {
+        Self {
+            asset_root: AssetRoot::default(),
+            extent: [WIDTH, HEIGHT],
+            gpu: None,
+            model: None,
+            texture: None,
+            up_axis: Some(UpAxis::Z),
+            unit_scale: Some(1.0),
+            latency: None,
+            present_mode: None,
+            frames_in_flight: None,
+            watch_shaders: false,
+            samples: 1,
+            front_face: Winding::CounterClockwise,
+            letterbox: Letterbox::Window,
+            clear_color: SrgbColor::BLACK,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameInput {
+    pub view: glm::Mat4,
+    pub model: glm::Mat4,
+}
+
+pub struct Renderer<W> {
+    graphics_queue: Arc<Queue>,
+    present_queue: Arc<Queue>,
+    swapchain: Arc<Swapchain<W>>,
+    swapchain_images: Vec<Arc<SwapchainImage<W>>>,
+    extent: [u32; 2],
+    swapchain_usage: ImageUsage,
+    latency: Option<Latency>,
+    present_mode: Option<PresentModePreference>,
+    frames_in_flight: Option<usize>,
+    render_pass: Arc<dyn RenderPassAbstract + Send + Sync>,
+    depth_format: Format,
+    samples: u32,
+    pipeline: Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+    wireframe_pipeline: Option<Arc<dyn GraphicsPipelineAbstract + Send + Sync>>,
+    pipeline_options: PipelineOptions,
+    wireframe: bool,
+    shaders: Option<Shaders>,
+    letterbox: Letterbox,
+    clear_color: SrgbColor,
+    dynamic_state: DynamicState,
+    framebuffers: Vec<Arc<dyn FramebufferAbstract + Send + Sync>>,
+    meshes: Vec<MeshBuffers>,
+    root_transform: glm::Mat4,
+    texture: Arc<ImmutableImage<Format>>,
+    uv_test_texture: Arc<ImmutableImage<Format>>,
+    uv_test_active: bool,
+    sampler: Arc<Sampler>,
+    uniform_buffer: CpuBufferPool<vs::ty::UniformBufferObject>,
+    descriptor_pool: FixedSizeDescriptorSetsPool,
+    swapchain_out_of_date: bool,
+    suspended: bool,
+    frames_since_recreate: u32,
+    suboptimal_ignored_logged: bool,
+    recreation_time: Option<Duration>,
+    last_present: Option<Instant>,
+    #[cfg(feature = "hot-reload")]
+    shader_watcher: Option<ShaderWatcher>,
+    screenshots_supported: bool,
+    screenshot_requested: bool,
+    pending_screenshot: Option<PendingScreenshot>,
+    frame_fences: Vec<Option<FrameFence>>,
+    frame_index: usize,
+}
+
+impl<W: Send + Sync + 'static> Renderer<W> {
+    pub fn with_surface(surface: Arc<Surface<W>>, settings: &RendererSettings) -> Result<Self> {
+        let (graphics_queue_family, present_queue_family) =
+            pick_queues_families(&surface, settings.gpu.as_deref())?;
+
+        let (device, graphics_queue, present_queue) =
+            create_device(graphics_queue_family, present_queue_family)?;
+
+        let requested_features = SwapchainFeatures { screenshots: true };
+        let (swapchain_usage, swapchain_features) = negotiate_usage(
+            requested_features,
+            surface
+                .capabilities(device.physical_device())?
+                .supported_usage_flags,
+        );
+        if requested_features.screenshots && !swapchain_features.screenshots {
+            warn!("Swapchain images can't be copied from, screenshots are disabled");
+        }
+
+        let (swapchain, swapchain_images, profile) = create_swapchain(
+            surface,
+            device.clone(),
+            graphics_queue.clone(),
+            present_queue.clone(),
+            settings.extent,
+            swapchain_usage,
+            settings.latency,
+            settings.present_mode,
+            settings.frames_in_flight,
+            None,
+        )?;
+
+        let screenshots_supported =
+            swapchain_features.screenshots && screenshot::is_supported_format(swapchain.format());
+
+        // The shaders output linear colors, which only an sRGB swapchain encodes for display
+        if !color::is_srgb_format(swapchain.format()) {
+            warn!(
+                "Swapchain format {:?} is not sRGB, shaded colors will look too dark",
+                swapchain.format()
+            );
+        }
+
+        let (meshes, root_transform) = create_buffers(
+            graphics_queue.clone(),
+            &settings.asset_root,
+            settings.model.as_deref(),
+            settings.up_axis,
+            settings.unit_scale,
+        )?;
+
+        let texture = load_texture(
+            graphics_queue.clone(),
+            &settings.asset_root,
+            settings.texture.as_deref(),
+        )?;
+
+        let uv_test_texture = create_uv_test_texture(graphics_queue.clone())?;
+
+        let sampler = create_sampler(device.clone())?;
+
+        let depth_format = find_depth_format(device.physical_device())?;
+
+        let samples = pick_sample_count(device.physical_device(), settings.samples);
+        if samples != settings.samples {
+            warn!("{} samples requested, using {samples}", settings.samples);
+        }
+
+        let render_pass =
+            create_render_pass(device.clone(), swapchain.clone(), depth_format, samples)?;
+
+        #[cfg(feature = "hot-reload")]
+        let (shader_watcher, shaders) = {
+            let shader_watcher = if settings.watch_shaders {
+                Some(ShaderWatcher::new(
+                    settings.asset_root.resolve("assets/shaders/shader.vert")?,
+                    settings.asset_root.resolve("assets/shaders/shader.frag")?,
+                ))
+            } else {
+                None
+            };
+
+            let shaders = match &shader_watcher {
+                Some(watcher) => {
+                    match load_shaders(device.clone(), watcher.vert_path(), watcher.frag_path()) {
+                        Ok(shaders) => Some(shaders),
+                        Err(e) => {
+                            error!("{e}, using the built-in shaders instead");
+                            None
+                        }
+                    }
+                }
+                None => None,
+            };
+            (shader_watcher, shaders)
+        };
+        #[cfg(not(feature = "hot-reload"))]
+        let shaders = {
+            if settings.watch_shaders {
+                warn!("Built without the hot-reload feature, using the built-in shaders instead");
+            }
+            None
+        };
+
+        let pipeline_options = PipelineOptions {
+            front_face: settings.front_face.front_face(),
+            ..PipelineOptions::default()
+        };
+        let (pipeline, wireframe_pipeline) =
+            create_pipelines(render_pass.clone(), shaders.as_ref(), pipeline_options)?;
+
+        let mut dynamic_state = DynamicState::none();
+        update_dynamic_viewport(swapchain.clone(), settings.letterbox, &mut dynamic_state);
+
+        let framebuffers = create_framebuffers(
+            swapchain_images.clone(),
+            render_pass.clone(),
+            depth_format,
+            samples,
+        )?;
+
+        let uniform_buffer = CpuBufferPool::<vs::ty::UniformBufferObject>::uniform_buffer(device);
+
+        let descriptor_pool =
+            FixedSizeDescriptorSetsPool::new(pipeline.descriptor_set_layout(0).unwrap().clone());
+
+        Ok(Self {
+            graphics_queue,
+            present_queue,
+            swapchain,
+            swapchain_images,
+            extent: settings.extent,
+            swapchain_usage,
+            latency: settings.latency,
+            present_mode: settings.present_mode,
+            frames_in_flight: settings.frames_in_flight,
+            render_pass,
+            depth_format,
+            samples,
+            pipeline,
+            wireframe_pipeline,
+            pipeline_options,
+            wireframe: false,
+            shaders,
+            letterbox: settings.letterbox,
+            clear_color: settings.clear_color,
+            dynamic_state,
+            framebuffers,
+            meshes,
+            root_transform,
+            texture,
+            uv_test_texture,
+            uv_test_active: false,
+            sampler,
+            uniform_buffer,
+            descriptor_pool,
+            swapchain_out_of_date: false,
+            suspended: false,
+            frames_since_recreate: 0,
+            suboptimal_ignored_logged: false,
+            recreation_time: None,
+            last_present: None,
+            #[cfg(feature = "hot-reload")]
+            shader_watcher,
+            screenshots_supported,
+            screenshot_requested: false,
+            pending_screenshot: None,
+            frame_fences: vec![None; profile.frames_in_flight],
+            frame_index: 0,
+        })
+    }
+
+    // A minimized window has a zero-sized surface, which no swapchain can be created for
+    pub fn resize(&mut self, extent: [u32; 2]) {
+        self.extent = extent;
+        self.swapchain_out_of_date = true;
+
+        let suspended = extent[0] == 0 || extent[1] == 0;
+        if suspended != self.suspended {
+            self.suspended = suspended;
+            if suspended {
+                info!("Surface minimized, rendering suspended");
+            } else {
+                info!("Surface restored, rendering resumed");
+            }
+        }
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    pub fn uv_test_active(&self) -> bool {
+        self.uv_test_active
+    }
+
+    pub fn toggle_uv_test(&mut self) {
+        self.uv_test_active = !self.uv_test_active;
+        info!(
+            "UV test pattern: {}",
+            if self.uv_test_active { "on" } else { "off" }
+        );
+    }
+
+    pub fn cycle_present_mode(&mut self) {
+        let current = self.present_mode.unwrap_or_else(|| {
+            PresentModePreference::from_present_mode(self.swapchain.present_mode())
+        });
+        self.present_mode = Some(current.next());
+        self.swapchain_out_of_date = true;
+    }
+
+    pub fn request_screenshot(&mut self) -> bool {
+        if !self.screenshots_supported {
+            warn!("Screenshots are not supported by this swapchain");
+        }
+        self.screenshot_requested = self.screenshots_supported;
+        self.screenshot_requested
+    }
+
+    pub fn toggle_culling(&mut self) {
+        let cull_mode = match self.pipeline_options.cull_mode {
+            CullMode::None => CullMode::Back,
+            _ => CullMode::None,
+        };
+        let pipeline_options = PipelineOptions {
+            cull_mode,
+            ..self.pipeline_options
+        };
+
+        match create_pipelines(
+            self.render_pass.clone(),
+            self.shaders.as_ref(),
+            pipeline_options,
+        ) {
+            Ok(pipelines) => {
+                self.set_pipelines(pipelines);
+                self.pipeline_options = pipeline_options;
+                info!("Backface culling: {cull_mode:?}");
+            }
+            Err(e) => error!("Failed to rebuild the pipeline: {e:?}"),
+        }
+    }
+
+    pub fn toggle_wireframe(&mut self) {
+        if self.wireframe_pipeline.is_none() {
+            warn!("Wireframe rendering is not supported by this device");
+            return;
+        }
+
+        self.wireframe = !self.wireframe;
+        info!("Wireframe: {}", if self.wireframe { "on" } else { "off" });
+    }
+
+    pub fn cycle_letterbox(&mut self) {
+        self.letterbox = self.letterbox.next();
+        update_dynamic_viewport(
+            self.swapchain.clone(),
+            self.letterbox,
+            &mut self.dynamic_state,
+        );
+        info!("Letterbox: {}", self.letterbox);
+    }
+
+    pub fn render_frame(&mut self, frame_time: FrameTime, input: FrameInput) -> Result<()> {
+        if self.suspended {
+            return Ok(());
+        }
+
+        if self.swapchain_out_of_date {
+            self.recreate_swapchain()?;
+        }
+
+        #[cfg(feature = "hot-reload")]
+        self.reload_changed_shaders();
+
+        if let Some(fence) = &self.frame_fences[self.frame_index] {
+            fence.wait(None)?;
+        }
+
+        match self.pending_screenshot.take() {
+            Some(screenshot) if screenshot.frame_index() == self.frame_index => screenshot.save(),
+            pending => self.pending_screenshot = pending,
+        }
+
+        let (image_num, suboptimal, acquire_future) =
+            match swapchain::acquire_next_image(self.swapchain.clone(), None) {
+                Ok(r) => r,
+                Err(AcquireError::OutOfDate) => return self.recreate_swapchain(),
+                Err(e) => return Err(eyre!("Failed to acquire next image: {e:?}")),
+            };
+
+        self.frames_since_recreate = self.frames_since_recreate.saturating_add(1);
+
+        let recreate_suboptimal = suboptimal && self.suboptimal_needs_recreation();
+
+        let set = self.update_descriptor_set(frame_time, input)?;
+
+        let mut builder = AutoCommandBufferBuilder::primary_one_time_submit(
+            self.pipeline.device().clone(),
+            self.graphics_queue.family(),
+        )?;
+
+        builder.begin_render_pass(
+            self.framebuffers[image_num].clone(),
+            SubpassContents::Inline,
+            clear_values(
+                color::attachment_color(self.clear_color, self.swapchain.format()),
+                self.depth_format,
+                self.samples,
+            ),
+        )?;
+
+        let pipeline = match &self.wireframe_pipeline {
+            Some(wireframe_pipeline) if self.wireframe => wireframe_pipeline.clone(),
+            _ => self.pipeline.clone(),
+        };
+
+        for (vertex_buffer, index_buffer) in &self.meshes {
+            match index_buffer {
+                IndexBuffer::U16(index_buffer) => builder.draw_indexed(
+                    pipeline.clone(),
+                    &self.dynamic_state,
+                    vec![vertex_buffer.clone()],
+                    index_buffer.clone(),
+                    set.clone(),
+                    (),
+                    vec![],
+                )?,
+                IndexBuffer::U32(index_buffer) => builder.draw_indexed(
+                    pipeline.clone(),
+                    &self.dynamic_state,
+                    vec![vertex_buffer.clone()],
+                    index_buffer.clone(),
+                    set.clone(),
+                    (),
+                    vec![],
+                )?,
+            };
+        }
+
+        builder.end_render_pass()?;
+
+        let screenshot = if self.screenshot_requested && self.pending_screenshot.is_none() {
+            self.screenshot_requested = false;
+
+            let screenshot = PendingScreenshot::new(
+                self.pipeline.device().clone(),
+                self.swapchain.dimensions(),
+                self.swapchain.format(),
+                self.frame_index,
+            )?;
+            builder.copy_image_to_buffer(
+                self.swapchain_images[image_num].clone(),
+                screenshot.buffer(),
+            )?;
+            Some(screenshot)
+        } else {
+            None
+        };
+
+        let command_buffer = builder.build()?;
+
+        let frame_count = self.frame_fences.len();
+        let previous_index = (self.frame_index + frame_count - 1) % frame_count;
+
+        let previous_future: Box<dyn GpuFuture + Send + Sync> =
+            match self.frame_fences[previous_index].clone() {
+                Some(fence) => Box::new(fence),
+                None => Box::new(sync::now(self.pipeline.device().clone())),
+            };
+
+        let future: Box<dyn GpuFuture + Send + Sync> = Box::new(
+            previous_future
+                .join(acquire_future)
+                .then_execute(self.graphics_queue.clone(), command_buffer)?
+                .then_swapchain_present(
+                    self.present_queue.clone(),
+                    self.swapchain.clone(),
+                    image_num,
+                ),
+        );
+
+        self.frame_fences[self.frame_index] = match future.then_signal_fence_and_flush() {
+            Ok(fence) => {
+                if screenshot.is_some() {
+                    self.pending_screenshot = screenshot;
+                }
+                self.log_recreation_hitch();
+                Some(Arc::new(fence))
+            }
+            Err(FlushError::OutOfDate) => {
+                self.swapchain_out_of_date = true;
+                None
+            }
+            Err(e) => {
+                error!("Failed to flush future: {e:?}");
+                None
+            }
+        };
+        self.frame_index = (self.frame_index + 1) % frame_count;
+
+        if self.swapchain_out_of_date || recreate_suboptimal {
+            self.recreate_swapchain()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "hot-reload")]
+    fn reload_changed_shaders(&mut self) {
+        let watcher = match &mut self.shader_watcher {
+            Some(watcher) => watcher,
+            None => return,
+        };
+        if !watcher.poll_changed() {
+            return;
+        }
+
+        // A shader that fails to compile or link leaves the previous pipeline running
+        match load_shaders(
+            self.pipeline.device().clone(),
+            watcher.vert_path(),
+            watcher.frag_path(),
+        )
+        .and_then(|shaders| {
+            let pipelines = create_pipelines(
+                self.render_pass.clone(),
+                Some(&shaders),
+                self.pipeline_options,
+            )?;
+            Ok((shaders, pipelines))
+        }) {
+            Ok((shaders, pipelines)) => {
+                self.shaders = Some(shaders);
+                self.set_pipelines(pipelines);
+                info!("Shaders reloaded");
+            }
+            Err(e) => error!("{e}, keeping the previous pipeline"),
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn set_pipelines(
+        &mut self,
+        (pipeline, wireframe_pipeline): (
+            Arc<dyn GraphicsPipelineAbstract + Send + Sync>,
+            Option<Arc<dyn GraphicsPipelineAbstract + Send + Sync>>,
+        ),
+    ) {
+        //
+        self.descriptor_pool =
+            FixedSizeDescriptorSetsPool::new(pipeline.descriptor_set_layout(0).unwrap().clone());
+        self.pipeline = pipeline;
+        self.wireframe_pipeline = wireframe_pipeline;
+    }
+
+    // The extent is the one cached from the last Resized event, querying the surface capabilities
+    // on every suboptimal frame costs a driver round trip per frame on affected compositors
+    fn suboptimal_needs_recreation(&mut self) -> bool {
+        let recreate = should_recreate_suboptimal(
+            self.extent,
+            self.swapchain.dimensions(),
+            self.frames_since_recreate,
+        );
+
+        if !recreate && !self.suboptimal_ignored_logged {
+            info!(
+                "Swapchain is suboptimal but matches the surface extent {:?}, \
+                 recreating it at most every {SUBOPTIMAL_RECREATE_INTERVAL} frames",
+                self.extent
+            );
+            self.suboptimal_ignored_logged = true;
+        }
+        recreate
+    }
+
+    // The interval between the last frame presented on the old swapchain and the first one on the
+    // new swapchain is the hitch seen on resize, compare it with the frame times in the title
+    fn log_recreation_hitch(&mut self) {
+        let now = Instant::now();
+        if let (Some(recreation_time), Some(last_present)) =
+            (self.recreation_time.take(), self.last_present)
+        {
+            info!(
+                "Swapchain recreated in {recreation_time:?}, frame interval {:?}",
+                now.duration_since(last_present)
+            );
+        }
+        self.last_present = Some(now);
+    }
+
+    // Nothing waits for the frames in flight here: the new swapchain is created from the old one
+    // while they are still executing, and their fences own the command buffers and present
+    // futures that keep the old images and framebuffers alive until they signal
+    pub fn recreate_swapchain(&mut self) -> Result<()> {
+        let start_instant = Instant::now();
+
+        // The frame fences are sized once, only the swapchain follows a changed present mode
+        let (new_swapchain, new_swapchain_images, _) = match create_swapchain(
+            self.swapchain.surface().clone(),
+            self.pipeline.device().clone(),
+            self.graphics_queue.clone(),
+            self.present_queue.clone(),
+            self.extent,
+            self.swapchain_usage,
+            self.latency,
+            self.present_mode,
+            self.frames_in_flight,
+            Some(self.swapchain.clone()),
+        ) {
+            Ok(r) => r,
+            Err(Error::SwapchainCreation(SwapchainCreationError::UnsupportedDimensions)) => {
+                return Ok(())
+            }
+            Err(e) => return Err(eyre!("Failed to recreate swapchain: {e:?}")),
+        };
+
+        let new_framebuffers = create_framebuffers(
+            new_swapchain_images.clone(),
+            self.render_pass.clone(),
+            self.depth_format,
+            self.samples,
+        )?;
+
+        // Everything is switched at once, a failure above leaves the old swapchain in use
+        self.swapchain = new_swapchain;
+        self.swapchain_images = new_swapchain_images;
+        self.framebuffers = new_framebuffers;
+
+        update_dynamic_viewport(
+            self.swapchain.clone(),
+            self.letterbox,
+            &mut self.dynamic_state,
+        );
+
+        self.swapchain_out_of_date = false;
+        self.frames_since_recreate = 0;
+        self.recreation_time = Some(start_instant.elapsed());
+        Ok(())
+    }
+
+    fn update_descriptor_set(
+        &mut self,
+        frame_time: FrameTime,
+        input: FrameInput,
+    ) -> Result<Arc<dyn DescriptorSet + Send + Sync>> {
+        //
+        let time = frame_time.shader_time();
+
+        let (_, dimensions) = self.letterbox.viewport(self.swapchain.dimensions());
+        let aspect_ratio = dimensions[0] / dimensions[1];
+
+        let model = input.model * self.root_transform;
+
+        let mut ubo = vs::ty::UniformBufferObject {
+            model: model.into(),
+
+            view: input.view.into(),
+
+            proj: glm::perspective(aspect_ratio, f32::to_radians(45.0), 0.1, 10.0).into(),
+
+            normal_matrix: glm::inverse_transpose(model).into(),
+
+            light_direction: LIGHT_DIRECTION,
+            light_color: LIGHT_COLOR.to_array(),
+            ambient: AMBIENT_LIGHT,
+
+            time,
+            delta_time: frame_time.delta as f32,
+        };
+        ubo.proj[1][1] *= -1.0;
+
+        // Both textures stay loaded, the descriptor set built each frame picks the active one
+        let texture = if self.uv_test_active {
+            self.uv_test_texture.clone()
+        } else {
+            self.texture.clone()
+        };
+
+        Ok(Arc::new(
+            self.descriptor_pool
+                .next()
+                .add_buffer(self.uniform_buffer.next(ubo)?)?
+                .add_sampled_image(ImageView::new(texture)?, self.sampler.clone())?
+                .build()?,
+        ))
+    }
+}
+
+// With fractional scaling some compositors report every frame as suboptimal, even right after a
+// recreation, so the swapchain is only recreated right away when the surface extent changed
+fn should_recreate_suboptimal(
+    current_extent: [u32; 2],
+    swapchain_extent: [u32; 2],
+    frames_since_recreate: u32,
+) -> bool {
+    //
+    current_extent != swapchain_extent || frames_since_recreate >= SUBOPTIMAL_RECREATE_INTERVAL
+}
+
+fn clear_values(color: [f32; 4], depth_format: Format, samples: u32) -> Vec<ClearValue> {
+    let color: ClearValue = color.into();
+
+    let depth: ClearValue = match depth_format.ty() {
+        FormatTy::DepthStencil => (1.0, 0).into(),
+        _ => 1.0.into(),
+    };
+
+    if samples > 1 {
+        vec![color, depth, ClearValue::None]
+    } else {
+        vec![color, depth]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_extent_is_recreated_right_away() {
+        assert!(should_recreate_suboptimal([1024, 768], [800, 600], 0));
+        assert!(should_recreate_suboptimal([800, 601], [800, 600], 1));
+    }
+
+    #[test]
+    fn matching_extent_is_recreated_periodically() {
+        assert!(!should_recreate_suboptimal([800, 600], [800, 600], 0));
+        assert!(!should_recreate_suboptimal(
+            [800, 600],
+            [800, 600],
+            SUBOPTIMAL_RECREATE_INTERVAL - 1
+        ));
+        assert!(should_recreate_suboptimal(
+            [800, 600],
+            [800, 600],
+            SUBOPTIMAL_RECREATE_INTERVAL
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    iter,
+    sync::Arc,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use vulkano::{
+    buffer::{BufferUsage, CpuAccessibleBuffer},
+    device::Device,
+    format::Format,
+};
+
+use image::ColorType;
+
+use log::{error, info};
+
+use color_eyre::Result;
+
+pub fn is_supported_format(format: Format) -> bool {
+    matches!(
+        format,
+        Format::B8G8R8A8Unorm | Format::B8G8R8A8Srgb | Format::R8G8B8A8Unorm | Format::R8G8B8A8Srgb
+    )
+}
+
+pub struct PendingScreenshot {
+    buffer: Arc<CpuAccessibleBuffer<[u8]>>,
+    dimensions: [u32; 2],
+    format: Format,
+    frame_index: usize,
+}
+
+impl PendingScreenshot {
+    pub fn new(
+        device: Arc<Device>,
+        dimensions: [u32; 2],
+        format: Format,
+        frame_index: usize,
+    ) -> Result<Self> {
+        //
+        // Image to buffer copies are tightly packed, so there is no row padding to strip
+        let size = (dimensions[0] * dimensions[1] * 4) as usize;
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device,
+            BufferUsage::transfer_destination(),
+            false,
+            iter::repeat_n(0u8, size),
+        )?;
+
+        Ok(Self {
+            buffer,
+            dimensions,
+            format,
+            frame_index,
+        })
+    }
+
+    pub fn buffer(&self) -> Arc<CpuAccessibleBuffer<[u8]>> {
+        self.buffer.clone()
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    // Must only be called once the fence of the frame which copied the image has signaled
+    pub fn save(self) {
+        let mut pixels = match self.buffer.read() {
+            Ok(pixels) => pixels.to_vec(),
+            Err(e) => {
+                error!("Failed to read screenshot: {e:?}");
+                return;
+            }
+        };
+
+        if matches!(self.format, Format::B8G8R8A8Unorm | Format::B8G8R8A8Srgb) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        let path = format!("screenshot-{timestamp}.png");
+        let [width, height] = self.dimensions;
+
+        // PNG encoding takes a while, keep it off the render thread
+        thread::spawn(move || {
+            match image::save_buffer(&path, &pixels, width, height, ColorType::Rgba8) {
+                Ok(()) => info!("Screenshot saved to {path}"),
+                Err(e) => error!("Failed to save screenshot {path}: {e:?}"),
+            }
+        });
+    }
+}
@@ -0,0 +1,159 @@
+#[cfg(feature = "hot-reload")]
+use crate::error::{Error, Result};
+use crate::{fs, vs};
+
+use std::{ffi::CStr, sync::Arc};
+#[cfg(feature = "hot-reload")]
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+#[cfg(feature = "hot-reload")]
+use vulkano::device::Device;
+use vulkano::{
+    descriptor::descriptor::ShaderStages,
+    pipeline::shader::{GraphicsEntryPoint, GraphicsShaderType, ShaderModule},
+};
+
+#[cfg(feature = "hot-reload")]
+use shaderc::ShaderKind;
+
+// Runtime compilation and the shader watcher need shaderc, which is only built with the
+// hot-reload feature. Without it the pipelines always use the baked-in shaders
+#[cfg(feature = "hot-reload")]
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Runtime shaders are bound with the interfaces generated for the baked-in ones,
+// so a reloaded shader must keep the same inputs, outputs and descriptor bindings
+pub struct Shaders {
+    vertex: Arc<ShaderModule>,
+    fragment: Arc<ShaderModule>,
+}
+
+impl Shaders {
+    pub fn vertex_entry_point(
+        &self,
+    ) -> GraphicsEntryPoint<'_, (), vs::MainInput, vs::MainOutput, vs::MainLayout> {
+        //
+        unsafe {
+            self.vertex.graphics_entry_point(
+                main_name(),
+                vs::MainInput,
+                vs::MainOutput,
+                vs::MainLayout(ShaderStages {
+                    vertex: true,
+                    ..ShaderStages::none()
+                }),
+                GraphicsShaderType::Vertex,
+            )
+        }
+    }
+
+    pub fn fragment_entry_point(
+        &self,
+    ) -> GraphicsEntryPoint<'_, (), fs::MainInput, fs::MainOutput, fs::MainLayout> {
+        //
+        unsafe {
+            self.fragment.graphics_entry_point(
+                main_name(),
+                fs::MainInput,
+                fs::MainOutput,
+                fs::MainLayout(ShaderStages {
+                    fragment: true,
+                    ..ShaderStages::none()
+                }),
+                GraphicsShaderType::Fragment,
+            )
+        }
+    }
+}
+
+fn main_name() -> &'static CStr {
+    c"main"
+}
+
+#[cfg(feature = "hot-reload")]
+pub fn load_shaders(device: Arc<Device>, vert_path: &Path, frag_path: &Path) -> Result<Shaders> {
+    let mut compiler = shaderc::Compiler::new().ok_or(Error::ShaderCompilerUnavailable)?;
+
+    let vertex = compile_shader(&mut compiler, device.clone(), vert_path, ShaderKind::Vertex)?;
+    let fragment = compile_shader(&mut compiler, device, frag_path, ShaderKind::Fragment)?;
+
+    Ok(Shaders { vertex, fragment })
+}
+
+#[cfg(feature = "hot-reload")]
+fn compile_shader(
+    compiler: &mut shaderc::Compiler,
+    device: Arc<Device>,
+    path: &Path,
+    kind: ShaderKind,
+) -> Result<Arc<ShaderModule>> {
+    //
+    let source = std::fs::read_to_string(path)?;
+
+    // shaderc prefixes each diagnostic with the file name and line given here
+    let artifact = compiler
+        .compile_into_spirv(&source, kind, &path.to_string_lossy(), "main", None)
+        .map_err(|e| Error::ShaderCompile {
+            file: path.to_owned(),
+            log: e.to_string(),
+        })?;
+
+    // The SPIR-V comes straight from shaderc, which only emits valid modules
+    Ok(unsafe { ShaderModule::new(device, artifact.as_binary_u8())? })
+}
+
+#[cfg(feature = "hot-reload")]
+pub struct ShaderWatcher {
+    vert_path: PathBuf,
+    frag_path: PathBuf,
+    modified: [Option<SystemTime>; 2],
+    next_poll: Instant,
+}
+
+#[cfg(feature = "hot-reload")]
+impl ShaderWatcher {
+    pub fn new(vert_path: PathBuf, frag_path: PathBuf) -> Self {
+        let modified = [modified_time(&vert_path), modified_time(&frag_path)];
+
+        Self {
+            vert_path,
+            frag_path,
+            modified,
+            next_poll: Instant::now() + POLL_INTERVAL,
+        }
+    }
+
+    pub fn vert_path(&self) -> &Path {
+        &self.vert_path
+    }
+
+    pub fn frag_path(&self) -> &Path {
+        &self.frag_path
+    }
+
+    pub fn poll_changed(&mut self) -> bool {
+        let now = Instant::now();
+        if now < self.next_poll {
+            return false;
+        }
+        self.next_poll = now + POLL_INTERVAL;
+
+        let modified = [
+            modified_time(&self.vert_path),
+            modified_time(&self.frag_path),
+        ];
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+}
+
+#[cfg(feature = "hot-reload")]
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
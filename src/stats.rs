@@ -0,0 +1,94 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+// Setting the window title is slow on some platforms (e.g. X11), so it is refreshed periodically
+const TITLE_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStatsSummary {
+    pub fps: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl FrameStatsSummary {
+    pub fn title(&self, base: &str) -> String {
+        format!(
+            "{base} - {:.1} FPS (min {:.2} ms, max {:.2} ms, p95 {:.2} ms)",
+            self.fps, self.min_ms, self.max_ms, self.p95_ms
+        )
+    }
+}
+
+// Frame durations are measured in wall time, independently of the (pausable) animation clock
+pub struct FrameStats {
+    frame_times: VecDeque<f64>,
+    sample_count: usize,
+    previous_frame: Option<Instant>,
+    next_title_update: Instant,
+}
+
+impl FrameStats {
+    pub fn new(sample_count: usize) -> Self {
+        let sample_count = sample_count.max(1);
+
+        Self {
+            frame_times: VecDeque::with_capacity(sample_count),
+            sample_count,
+            previous_frame: None,
+            next_title_update: Instant::now() + TITLE_UPDATE_INTERVAL,
+        }
+    }
+
+    pub fn record_frame(&mut self) {
+        let now = Instant::now();
+
+        if let Some(previous_frame) = self.previous_frame {
+            if self.frame_times.len() == self.sample_count {
+                self.frame_times.pop_front();
+            }
+            self.frame_times
+                .push_back(now.duration_since(previous_frame).as_secs_f64());
+        }
+        self.previous_frame = Some(now);
+    }
+
+    // The host resets the stats while suspended and when focus changes the frame cap, frame times
+    // from before and after would skew them
+    pub fn reset(&mut self) {
+        self.frame_times.clear();
+        self.previous_frame = None;
+    }
+
+    pub fn summary(&self) -> Option<FrameStatsSummary> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<_> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+
+        let average = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        let p95_index = ((sorted.len() as f64 * 0.95).ceil() as usize).clamp(1, sorted.len()) - 1;
+
+        Some(FrameStatsSummary {
+            fps: if average > 0.0 { 1.0 / average } else { 0.0 },
+            min_ms: sorted[0] * 1000.0,
+            max_ms: sorted[sorted.len() - 1] * 1000.0,
+            p95_ms: sorted[p95_index] * 1000.0,
+        })
+    }
+
+    pub fn title_update_due(&mut self) -> Option<FrameStatsSummary> {
+        let now = Instant::now();
+        if now < self.next_title_update {
+            return None;
+        }
+
+        self.next_title_update = now + TITLE_UPDATE_INTERVAL;
+        self.summary()
+    }
+}
@@ -0,0 +1,172 @@
+use std::{f64::consts::TAU, str::FromStr};
+
+use nalgebra_glm as glm;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Axis(pub glm::Vec3);
+
+impl FromStr for Axis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Axis(glm::vec3(1.0, 0.0, 0.0))),
+            "y" => Ok(Axis(glm::vec3(0.0, 1.0, 0.0))),
+            "z" => Ok(Axis(glm::vec3(0.0, 0.0, 1.0))),
+            _ => {
+                let components = s
+                    .split(',')
+                    .map(|c| c.trim().parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("expected x, y, z or a vector like 0,0,1 ({e})"))?;
+
+                match components[..] {
+                    [x, y, z] if glm::length(&glm::vec3(x, y, z)) > f32::EPSILON => {
+                        Ok(Axis(glm::normalize(&glm::vec3(x, y, z))))
+                    }
+                    _ => Err(format!(
+                        "expected x, y, z or a non-zero vector like 0,0,1, got {s}"
+                    )),
+                }
+            }
+        }
+    }
+}
+
+pub struct Turntable {
+    axis: glm::Vec3,
+    speed: f64,
+    swing: f64,
+    idle_delay: f64,
+    idle_time: f64,
+    time_offset: f64,
+    engaged_time: f64,
+}
+
+impl Turntable {
+    pub fn new(axis: Axis, speed: f64, swing: f64, idle_delay: f64, time_offset: f64) -> Self {
+        Self {
+            axis: axis.0,
+            speed: speed.to_radians(),
+            swing: swing.to_radians(),
+            idle_delay,
+            idle_time: 0.0,
+            time_offset,
+            engaged_time: time_offset,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.engaged_time = self.time_offset;
+    }
+
+    pub fn interrupt(&mut self) {
+        self.idle_time = 0.0;
+    }
+
+    // The idle delay is wall time, so pausing or slowing down the animation doesn't stretch it
+    pub fn update(&mut self, delta: f64, wall_delta: f64) {
+        self.idle_time += wall_delta;
+        if self.idle_time >= self.idle_delay {
+            self.engaged_time += delta;
+        }
+    }
+
+    pub fn angle(&self) -> f64 {
+        if self.swing > 0.0 {
+            // Sinusoidal swing whose peak angular speed matches the configured speed
+            self.swing * (self.engaged_time * self.speed / self.swing).sin()
+        } else {
+            (self.engaged_time * self.speed) % TAU
+        }
+    }
+
+    pub fn transform(&self) -> glm::Mat4 {
+        glm::rotate(&glm::identity(), self.angle() as f32, &self.axis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn z() -> Axis {
+        Axis(glm::vec3(0.0, 0.0, 1.0))
+    }
+
+    fn assert_angle(turntable: &Turntable, expected: f64) {
+        let angle = turntable.angle();
+        assert!((angle - expected).abs() < 1e-9, "{angle} != {expected}");
+    }
+
+    #[test]
+    fn full_spin_follows_the_speed() {
+        let mut turntable = Turntable::new(z(), 90.0, 0.0, 0.0, 0.0);
+
+        turntable.update(1.0, 1.0);
+        assert_angle(&turntable, 90f64.to_radians());
+
+        turntable.update(4.0, 4.0);
+        assert_angle(&turntable, 90f64.to_radians());
+    }
+
+    #[test]
+    fn swing_peaks_at_its_amplitude() {
+        let (speed, swing) = (90f64.to_radians(), 30f64.to_radians());
+        let mut turntable = Turntable::new(z(), 90.0, 30.0, 0.0, 0.0);
+
+        // Starts at the configured speed
+        turntable.update(1e-4, 1e-4);
+        assert!((turntable.angle() / 1e-4 - speed).abs() < 1e-6);
+
+        let quarter_period = swing / speed * std::f64::consts::FRAC_PI_2;
+        turntable.update(quarter_period - 1e-4, 0.0);
+        assert_angle(&turntable, swing);
+
+        for _ in 0..100 {
+            turntable.update(0.05, 0.05);
+            assert!(turntable.angle().abs() <= swing + 1e-9);
+        }
+    }
+
+    #[test]
+    fn interruption_waits_for_the_idle_delay() {
+        let mut turntable = Turntable::new(z(), 90.0, 0.0, 2.0, 0.0);
+
+        turntable.interrupt();
+        turntable.update(1.0, 1.0);
+        assert_angle(&turntable, 0.0);
+
+        turntable.update(1.0, 1.0);
+        assert_angle(&turntable, 90f64.to_radians());
+    }
+
+    #[test]
+    fn idle_delay_runs_on_wall_time() {
+        let mut turntable = Turntable::new(z(), 90.0, 0.0, 2.0, 0.0);
+        turntable.interrupt();
+
+        // Paused animation, the idle delay still elapses
+        turntable.update(0.0, 2.5);
+        assert_angle(&turntable, 0.0);
+
+        // Fast animation, the idle delay isn't shortened
+        let mut fast = Turntable::new(z(), 90.0, 0.0, 2.0, 0.0);
+        fast.interrupt();
+        fast.update(16.0, 1.0);
+        assert_angle(&fast, 0.0);
+
+        turntable.update(1.0, 1.0);
+        assert_angle(&turntable, 90f64.to_radians());
+    }
+
+    #[test]
+    fn reset_returns_to_the_time_offset() {
+        let mut turntable = Turntable::new(z(), 90.0, 0.0, 0.0, 1.0);
+        turntable.update(0.5, 0.5);
+        assert_angle(&turntable, 135f64.to_radians());
+
+        turntable.reset();
+        assert_angle(&turntable, 90f64.to_radians());
+    }
+}